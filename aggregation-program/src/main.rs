@@ -0,0 +1,33 @@
+//! Aggregates existing SXG proofs into one.
+//!
+//! Every proof is a compressed proof of the SXG program, verified here against the single SXG
+//! verifying key read from stdin. The committed digests let a verifier check any of the original
+//! public values against the aggregate.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+
+use lib::{sha256_hash, vkey_hash_bytes, AggregationPublicValuesStruct};
+pub fn main() {
+    // The SXG program's verifying key as `vk.hash_u32()`, shared by every proof.
+    let sxg_vkey = sp1_zkvm::io::read::<[u32; 8]>();
+    let public_values = sp1_zkvm::io::read::<Vec<Vec<u8>>>();
+
+    let public_values_digests = public_values
+        .iter()
+        .map(|public_values| {
+            let digest = sha256_hash(public_values);
+            sp1_zkvm::lib::verify::verify_sp1_proof(&sxg_vkey, &digest);
+            digest.into()
+        })
+        .collect();
+
+    let bytes = AggregationPublicValuesStruct::abi_encode(&AggregationPublicValuesStruct {
+        sxg_vkey_hash: vkey_hash_bytes(&sxg_vkey).into(),
+        public_values_digests,
+    });
+
+    sp1_zkvm::io::commit_slice(&bytes);
+}
@@ -26,6 +26,11 @@ interface ISP1VerifierWithHash is ISP1Verifier {
 struct PublicValuesStruct {
     uint32 result;
     uint8[] data_to_verify;
+    bytes32 signature_hash;
+    bytes public_key;
+    string url;
+    /// 0 = high-S accepted, 1 = high-S rejected, 2 = accepted with a low-S signature_hash.
+    uint8 low_s_policy;
 }
 
 /// @title Sxg.
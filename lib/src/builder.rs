@@ -0,0 +1,501 @@
+//! Host-side construction of [`SXGInput`]s from an exchange, working out every byte offset.
+//!
+//! The builder takes either a raw `.sxg` file through [`SXGInputBuilder::exchange`], or the signed
+//! message, decoded payload and signature separately.
+
+use std::{fmt, ops::Range};
+
+use crate::{
+    exchange::{decode_mi_sha256, Exchange, ExchangeError},
+    sxg::{mi_sha256_digest, SXGInput, MI_SHA256_RECORD_SIZE},
+    LowSPolicy, SignatureAlgorithm,
+};
+
+/// Which part of the response payload to disclose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Disclosure {
+    /// A byte string that must occur exactly once in the payload.
+    Literal(Vec<u8>),
+    /// An explicit byte range of the payload.
+    Range(Range<usize>),
+    /// The `n`th (zero-based) occurrence of a byte string in the payload.
+    Occurrence { needle: Vec<u8>, n: usize },
+}
+
+impl Disclosure {
+    pub fn literal(text: impl AsRef<[u8]>) -> Self {
+        Disclosure::Literal(text.as_ref().to_vec())
+    }
+
+    pub fn occurrence(needle: impl AsRef<[u8]>, n: usize) -> Self {
+        Disclosure::Occurrence {
+            needle: needle.as_ref().to_vec(),
+            n,
+        }
+    }
+
+    /// Resolves the disclosure to a byte range of `payload`.
+    pub fn locate(&self, payload: &[u8]) -> Result<Range<usize>, BuildError> {
+        match self {
+            Disclosure::Literal(needle) | Disclosure::Occurrence { needle, .. }
+                if needle.is_empty() =>
+            {
+                Err(BuildError::EmptyDisclosure)
+            }
+            Disclosure::Literal(needle) => match find_all(payload, needle)[..] {
+                [] => Err(BuildError::DisclosureNotFound),
+                [start] => Ok(start..start + needle.len()),
+                ref starts => Err(BuildError::AmbiguousDisclosure {
+                    occurrences: starts.len(),
+                }),
+            },
+            Disclosure::Range(range) => {
+                if range.start >= range.end || range.end > payload.len() {
+                    return Err(BuildError::RangeOutOfBounds {
+                        range: range.clone(),
+                        len: payload.len(),
+                    });
+                }
+                Ok(range.clone())
+            }
+            Disclosure::Occurrence { needle, n } => {
+                let starts = find_all(payload, needle);
+                match starts.get(*n) {
+                    Some(&start) => Ok(start..start + needle.len()),
+                    None => Err(BuildError::OccurrenceNotFound {
+                        n: *n,
+                        occurrences: starts.len(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    MissingField(&'static str),
+    EmptyDisclosure,
+    DisclosureNotFound,
+    AmbiguousDisclosure {
+        occurrences: usize,
+    },
+    OccurrenceNotFound {
+        n: usize,
+        occurrences: usize,
+    },
+    RangeOutOfBounds {
+        range: Range<usize>,
+        len: usize,
+    },
+    DigestNotFound,
+    AmbiguousDigest {
+        occurrences: usize,
+    },
+    Exchange(ExchangeError),
+    /// The exchange has no signature with the requested label.
+    SignatureNotFound(Option<String>),
+    /// The exchange's payload uses a record size the guest does not support.
+    UnsupportedRecordSize(usize),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingField(field) => write!(f, "`{}` was not set", field),
+            BuildError::EmptyDisclosure => write!(f, "the disclosure is empty"),
+            BuildError::DisclosureNotFound => {
+                write!(f, "the disclosure does not occur in the payload")
+            }
+            BuildError::AmbiguousDisclosure { occurrences } => write!(
+                f,
+                "the disclosure occurs {} times in the payload; pick one with an occurrence or range",
+                occurrences
+            ),
+            BuildError::OccurrenceNotFound { n, occurrences } => write!(
+                f,
+                "occurrence {} requested but the payload only has {}",
+                n, occurrences
+            ),
+            BuildError::RangeOutOfBounds { range, len } => write!(
+                f,
+                "range {:?} is empty or outside the {}-byte payload",
+                range, len
+            ),
+            BuildError::DigestNotFound => write!(
+                f,
+                "the signed headers do not carry the payload's mi-sha256 digest"
+            ),
+            BuildError::AmbiguousDigest { occurrences } => write!(
+                f,
+                "the payload's mi-sha256 digest occurs {} times in the signed message",
+                occurrences
+            ),
+            BuildError::Exchange(e) => write!(f, "{}", e),
+            BuildError::SignatureNotFound(None) => write!(f, "the exchange has no signature"),
+            BuildError::SignatureNotFound(Some(label)) => {
+                write!(f, "the exchange has no signature labelled `{}`", label)
+            }
+            BuildError::UnsupportedRecordSize(record_size) => write!(
+                f,
+                "the payload uses mi-sha256 records of {} bytes; only {} is supported",
+                record_size, MI_SHA256_RECORD_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<ExchangeError> for BuildError {
+    fn from(e: ExchangeError) -> Self {
+        BuildError::Exchange(e)
+    }
+}
+
+/// The offsets of every occurrence of `needle` in `haystack`, overlapping ones included.
+pub(crate) fn find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Builds an [`SXGInput`], locating the disclosure in the payload and the payload's digest in the
+/// signed message.
+#[derive(Debug, Default)]
+pub struct SXGInputBuilder {
+    exchange: Option<(Vec<u8>, Option<String>)>,
+    final_payload: Option<Vec<u8>>,
+    payload: Option<Vec<u8>>,
+    disclosure: Option<Disclosure>,
+    algorithm: SignatureAlgorithm,
+    r: Vec<u8>,
+    s: Vec<u8>,
+    sig: Option<Vec<u8>>,
+    public_key: Option<Vec<u8>>,
+    low_s: LowSPolicy,
+}
+
+impl SXGInputBuilder {
+    /// A raw `application/signed-exchange;v=b3` file. The signed message, decoded payload and
+    /// DER signature are taken from it, using its first signature, unless set explicitly.
+    pub fn exchange(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.exchange = Some((bytes.into(), None));
+        self
+    }
+
+    /// Like [`SXGInputBuilder::exchange`], using the signature labelled `label`.
+    pub fn exchange_signature(mut self, bytes: impl Into<Vec<u8>>, label: &str) -> Self {
+        self.exchange = Some((bytes.into(), Some(label.to_string())));
+        self
+    }
+
+    /// The signed message: the SXG signature preamble followed by the request URL and the
+    /// CBOR-encoded response headers.
+    pub fn final_payload(mut self, final_payload: impl Into<Vec<u8>>) -> Self {
+        self.final_payload = Some(final_payload.into());
+        self
+    }
+
+    /// The decoded response body.
+    pub fn payload(mut self, payload: impl Into<Vec<u8>>) -> Self {
+        self.payload = Some(payload.into());
+        self
+    }
+
+    pub fn disclose(mut self, disclosure: Disclosure) -> Self {
+        self.disclosure = Some(disclosure);
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: SignatureAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the signature from its raw, fixed-width components.
+    pub fn signature_r_s(mut self, r: impl Into<Vec<u8>>, s: impl Into<Vec<u8>>) -> Self {
+        self.r = r.into();
+        self.s = s.into();
+        self
+    }
+
+    /// Sets the signature from the DER-encoded SXG `sig` parameter.
+    pub fn signature_der(mut self, sig: impl Into<Vec<u8>>) -> Self {
+        self.sig = Some(sig.into());
+        self
+    }
+
+    /// Sets the signing key in any form [`SXGInput::public_key`] accepts.
+    pub fn public_key(mut self, public_key: impl Into<Vec<u8>>) -> Self {
+        self.public_key = Some(public_key.into());
+        self
+    }
+
+    pub fn low_s(mut self, low_s: LowSPolicy) -> Self {
+        self.low_s = low_s;
+        self
+    }
+
+    /// Fills in whatever was not set explicitly from the raw exchange.
+    fn apply_exchange(mut self) -> Result<Self, BuildError> {
+        let Some((bytes, label)) = self.exchange.take() else {
+            return Ok(self);
+        };
+        let exchange = Exchange::parse(&bytes)?;
+        let signatures = exchange.signatures()?;
+        let params = match &label {
+            None => signatures.first(),
+            Some(label) => signatures.iter().find(|params| &params.label == label),
+        }
+        .ok_or(BuildError::SignatureNotFound(label))?;
+
+        if self.payload.is_none() {
+            let (record_size, payload) = decode_mi_sha256(exchange.payload)?;
+            if record_size != MI_SHA256_RECORD_SIZE && payload.len() > record_size {
+                return Err(BuildError::UnsupportedRecordSize(record_size));
+            }
+            self.payload = Some(payload);
+        }
+        if self.final_payload.is_none() {
+            self.final_payload = Some(exchange.signed_message(params));
+        }
+        if self.sig.is_none() && self.r.is_empty() && self.s.is_empty() {
+            self.sig = Some(params.sig.clone());
+        }
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<SXGInput, BuildError> {
+        let builder = self.apply_exchange()?;
+        let final_payload = builder
+            .final_payload
+            .ok_or(BuildError::MissingField("final_payload"))?;
+        let payload = builder.payload.ok_or(BuildError::MissingField("payload"))?;
+        let disclosure = builder
+            .disclosure
+            .ok_or(BuildError::MissingField("disclosure"))?;
+        if builder.sig.is_none() && (builder.r.is_empty() || builder.s.is_empty()) {
+            return Err(BuildError::MissingField("signature"));
+        }
+        let public_key = builder
+            .public_key
+            .ok_or(BuildError::MissingField("public_key"))?;
+
+        let range = disclosure.locate(&payload)?;
+
+        let integrity_start_index = match find_all(&final_payload, &mi_sha256_digest(&payload))[..]
+        {
+            [] => return Err(BuildError::DigestNotFound),
+            [start] => start,
+            ref starts => {
+                return Err(BuildError::AmbiguousDigest {
+                    occurrences: starts.len(),
+                })
+            }
+        };
+
+        Ok(SXGInput {
+            data_to_verify: payload[range.clone()].to_vec(),
+            data_to_verify_start_index: range.start,
+            integrity_start_index,
+            final_payload,
+            payload,
+            algorithm: builder.algorithm,
+            r: builder.r,
+            s: builder.s,
+            sig: builder.sig,
+            px: Vec::new(),
+            py: Vec::new(),
+            public_key: Some(public_key),
+            low_s: builder.low_s,
+        })
+    }
+}
+
+impl SXGInput {
+    pub fn builder() -> SXGInputBuilder {
+        SXGInputBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::tests::{encode_mi_sha256, exchange_testcase_1_signed};
+
+    fn builder_for(input: &SXGInput) -> SXGInputBuilder {
+        SXGInput::builder()
+            .final_payload(input.final_payload.clone())
+            .payload(input.payload.clone())
+            .signature_r_s(input.r.clone(), input.s.clone())
+            .public_key(input.public_key().unwrap())
+    }
+
+    #[test]
+    fn test_builder_matches_testcases() {
+        for expected in [
+            SXGInput::default_testcase_1(),
+            SXGInput::default_testcase_2(),
+        ] {
+            let start = expected.data_to_verify_start_index;
+            let range = start..start + expected.data_to_verify.len();
+
+            let input = builder_for(&expected)
+                .disclose(Disclosure::Range(range))
+                .build()
+                .unwrap();
+            assert_eq!(input.integrity_start_index, expected.integrity_start_index);
+            assert_eq!(
+                input.data_to_verify_start_index,
+                expected.data_to_verify_start_index
+            );
+            assert_eq!(input.data_to_verify, expected.data_to_verify);
+            assert!(input.verify().unwrap());
+        }
+
+        let expected = SXGInput::default_testcase_2();
+        let input = builder_for(&expected)
+            .disclose(Disclosure::Literal(expected.data_to_verify.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(input.data_to_verify_start_index, 7504);
+        assert!(input.verify().unwrap());
+    }
+
+    /// DER-encodes a raw `(r, s)` signature as an `ECDSA-Sig-Value`.
+    fn der_signature(r: &[u8], s: &[u8]) -> Vec<u8> {
+        let integer = |value: &[u8]| {
+            let value = &value[value.iter().take_while(|b| **b == 0).count()..];
+            let pad = value.first().is_some_and(|b| b & 0x80 != 0);
+            let mut der = vec![0x02, (value.len() + pad as usize) as u8];
+            der.extend(pad.then_some(0));
+            der.extend(value);
+            der
+        };
+        let body = [integer(r), integer(s)].concat();
+        [vec![0x30, body.len() as u8], body].concat()
+    }
+
+    #[test]
+    fn test_builder_from_exchange() {
+        let expected = SXGInput::default_testcase_1();
+        let sig = der_signature(&expected.r, &expected.s);
+        let encoded = encode_mi_sha256(&expected.payload, MI_SHA256_RECORD_SIZE);
+        let bytes = exchange_testcase_1_signed(&encoded, &sig);
+
+        let input = SXGInput::builder()
+            .exchange(bytes.clone())
+            .public_key(expected.public_key().unwrap())
+            .disclose(Disclosure::Literal(expected.data_to_verify.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(input.final_payload, expected.final_payload);
+        assert_eq!(input.payload, expected.payload);
+        assert_eq!(input.integrity_start_index, expected.integrity_start_index);
+        assert_eq!(input.sig, Some(sig));
+        assert_eq!(
+            input.signature().unwrap(),
+            (expected.r.clone(), expected.s.clone())
+        );
+        assert!(input.verify().unwrap());
+
+        let result = SXGInput::builder()
+            .exchange_signature(bytes, "other")
+            .public_key(expected.public_key().unwrap())
+            .disclose(Disclosure::Range(0..3))
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            BuildError::SignatureNotFound(Some("other".to_string()))
+        );
+
+        let small_records =
+            exchange_testcase_1_signed(&encode_mi_sha256(&expected.payload, 4096), &[]);
+        let result = SXGInput::builder()
+            .exchange(small_records)
+            .disclose(Disclosure::Range(0..3))
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::UnsupportedRecordSize(4096));
+
+        let result = SXGInput::builder()
+            .exchange(b"not an exchange".to_vec())
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            BuildError::Exchange(ExchangeError::BadMagic)
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_bad_disclosures() {
+        let expected = SXGInput::default_testcase_1();
+
+        let result = builder_for(&expected)
+            .disclose(Disclosure::literal("not in the page at all"))
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::DisclosureNotFound);
+
+        let result = builder_for(&expected)
+            .disclose(Disclosure::literal("<div"))
+            .build();
+        assert!(matches!(
+            result.unwrap_err(),
+            BuildError::AmbiguousDisclosure { .. }
+        ));
+
+        let input = builder_for(&expected)
+            .disclose(Disclosure::occurrence("<div", 1))
+            .build()
+            .unwrap();
+        assert_eq!(input.data_to_verify, b"<div");
+        assert!(input.verify().unwrap());
+
+        let result = builder_for(&expected)
+            .disclose(Disclosure::occurrence("<title>", 5))
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            BuildError::OccurrenceNotFound {
+                n: 5,
+                occurrences: 4
+            }
+        );
+
+        let len = expected.payload.len();
+        let result = builder_for(&expected)
+            .disclose(Disclosure::Range(len - 1..len + 1))
+            .build();
+        assert!(matches!(
+            result.unwrap_err(),
+            BuildError::RangeOutOfBounds { .. }
+        ));
+
+        let result = builder_for(&expected)
+            .disclose(Disclosure::literal(""))
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::EmptyDisclosure);
+
+        let mut tampered = expected.payload.clone();
+        tampered[0] ^= 1;
+        let result = builder_for(&expected)
+            .payload(tampered)
+            .disclose(Disclosure::Range(0..3))
+            .build();
+        assert_eq!(result.unwrap_err(), BuildError::DigestNotFound);
+
+        let result = SXGInput::builder()
+            .disclose(Disclosure::Range(0..3))
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            BuildError::MissingField("final_payload")
+        );
+    }
+}
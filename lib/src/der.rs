@@ -0,0 +1,215 @@
+//! Strict DER decoding for the ECDSA signatures carried in the SXG `sig` parameter.
+//!
+//! The signature is an `ECDSA-Sig-Value ::= SEQUENCE { r INTEGER, s INTEGER }`. Only the canonical
+//! encoding is accepted: definite, minimal lengths, minimal positive integers and no trailing bytes.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerError {
+    UnexpectedEnd,
+    UnexpectedTag { expected: u8, found: u8 },
+    NonMinimalLength,
+    UnsupportedLength,
+    TrailingBytes,
+    EmptyInteger,
+    NegativeInteger,
+    NonMinimalInteger,
+    ZeroInteger,
+    IntegerTooLarge { max: usize, found: usize },
+}
+
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DerError::UnexpectedEnd => write!(f, "unexpected end of DER input"),
+            DerError::UnexpectedTag { expected, found } => {
+                write!(
+                    f,
+                    "expected DER tag {:#04x}, found {:#04x}",
+                    expected, found
+                )
+            }
+            DerError::NonMinimalLength => write!(f, "DER length is not minimally encoded"),
+            DerError::UnsupportedLength => write!(f, "unsupported DER length encoding"),
+            DerError::TrailingBytes => write!(f, "trailing bytes after DER value"),
+            DerError::EmptyInteger => write!(f, "DER integer has no content"),
+            DerError::NegativeInteger => write!(f, "DER integer is negative"),
+            DerError::NonMinimalInteger => write!(f, "DER integer is not minimally encoded"),
+            DerError::ZeroInteger => write!(f, "signature component is zero"),
+            DerError::IntegerTooLarge { max, found } => {
+                write!(f, "integer is {} bytes, at most {} allowed", found, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DerError {}
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// Reads one tag-length-value from the front of `input`, returning the value and the rest.
+fn read_tlv(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), DerError> {
+    let (&found, rest) = input.split_first().ok_or(DerError::UnexpectedEnd)?;
+    if found != tag {
+        return Err(DerError::UnexpectedTag {
+            expected: tag,
+            found,
+        });
+    }
+
+    let (&first, rest) = rest.split_first().ok_or(DerError::UnexpectedEnd)?;
+    let (len, rest) = match first {
+        0..=0x7f => (first as usize, rest),
+        // A single length byte covers every ECDSA signature we care about; anything longer, and the
+        // indefinite form (0x80), is not valid here.
+        0x81 => {
+            let (&len, rest) = rest.split_first().ok_or(DerError::UnexpectedEnd)?;
+            if len < 0x80 {
+                return Err(DerError::NonMinimalLength);
+            }
+            (len as usize, rest)
+        }
+        _ => return Err(DerError::UnsupportedLength),
+    };
+
+    if rest.len() < len {
+        return Err(DerError::UnexpectedEnd);
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Strips the sign byte of a positive DER integer, rejecting any non-canonical form.
+fn read_unsigned_integer(input: &[u8]) -> Result<(&[u8], &[u8]), DerError> {
+    let (value, rest) = read_tlv(input, TAG_INTEGER)?;
+    match value {
+        [] => Err(DerError::EmptyInteger),
+        [b, ..] if b & 0x80 != 0 => Err(DerError::NegativeInteger),
+        [0] => Err(DerError::ZeroInteger),
+        [0, b, ..] if b & 0x80 == 0 => Err(DerError::NonMinimalInteger),
+        [0, tail @ ..] => Ok((tail, rest)),
+        _ => Ok((value, rest)),
+    }
+}
+
+/// Parses a DER `ECDSA-Sig-Value` into the big-endian bytes of `r` and `s`, without leading zeros.
+pub fn parse_ecdsa_sig_value(der: &[u8]) -> Result<(&[u8], &[u8]), DerError> {
+    let (body, rest) = read_tlv(der, TAG_SEQUENCE)?;
+    if !rest.is_empty() {
+        return Err(DerError::TrailingBytes);
+    }
+
+    let (r, body) = read_unsigned_integer(body)?;
+    let (s, body) = read_unsigned_integer(body)?;
+    if !body.is_empty() {
+        return Err(DerError::TrailingBytes);
+    }
+
+    Ok((r, s))
+}
+
+/// Left-pads a big-endian integer to exactly `field_size` bytes.
+fn pad_be(value: &[u8], field_size: usize) -> Result<Vec<u8>, DerError> {
+    if value.len() > field_size {
+        return Err(DerError::IntegerTooLarge {
+            max: field_size,
+            found: value.len(),
+        });
+    }
+    let mut out = vec![0u8; field_size - value.len()];
+    out.extend_from_slice(value);
+    Ok(out)
+}
+
+/// Decodes a DER signature into `r` and `s`, each left-padded to `field_size` bytes.
+pub fn decode_ecdsa_signature(
+    der: &[u8],
+    field_size: usize,
+) -> Result<(Vec<u8>, Vec<u8>), DerError> {
+    let (r, s) = parse_ecdsa_sig_value(der)?;
+    Ok((pad_be(r, field_size)?, pad_be(s, field_size)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R1: &str = "9970818CBCA38C196795EEAD295BDED48311702DF7DDB0C2BB448276894C393D";
+    const S1: &str = "729B2F9229D545A553F0F7CBC1792E9A6185E539DBF667FE5BC38D673D90C014";
+
+    fn der_testcase_1() -> Vec<u8> {
+        let mut der = vec![0x30, 0x45, 0x02, 0x21, 0x00];
+        der.extend(hex::decode(R1).unwrap());
+        der.extend([0x02, 0x20]);
+        der.extend(hex::decode(S1).unwrap());
+        der
+    }
+
+    #[test]
+    fn test_decode_ecdsa_signature() {
+        let (r, s) = decode_ecdsa_signature(&der_testcase_1(), 32).unwrap();
+        assert_eq!(r, hex::decode(R1).unwrap());
+        assert_eq!(s, hex::decode(S1).unwrap());
+
+        // Short integers are left-padded.
+        let (r, s) =
+            decode_ecdsa_signature(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x7f], 32).unwrap();
+        assert_eq!(r[31], 1);
+        assert_eq!(s[31], 0x7f);
+        assert!(r[..31].iter().chain(&s[..31]).all(|b| *b == 0));
+
+        let (r, s) = decode_ecdsa_signature(&der_testcase_1(), 48).unwrap();
+        assert_eq!(r[16..].to_vec(), hex::decode(R1).unwrap());
+        assert_eq!(s[16..].to_vec(), hex::decode(S1).unwrap());
+        assert!(decode_ecdsa_signature(&der_testcase_1(), 16).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_non_canonical() {
+        let der = der_testcase_1();
+
+        let mut trailing = der.clone();
+        trailing.push(0);
+        assert_eq!(
+            decode_ecdsa_signature(&trailing, 32),
+            Err(DerError::TrailingBytes)
+        );
+
+        let mut long_form = vec![0x30, 0x81, 0x45];
+        long_form.extend(&der[2..]);
+        assert_eq!(
+            decode_ecdsa_signature(&long_form, 32),
+            Err(DerError::NonMinimalLength)
+        );
+
+        let mut missing_sign_byte = vec![0x30, 0x44, 0x02, 0x20];
+        missing_sign_byte.extend(&der[5..]);
+        assert_eq!(
+            decode_ecdsa_signature(&missing_sign_byte, 32),
+            Err(DerError::NegativeInteger)
+        );
+
+        let mut padded = vec![0x30, 0x46];
+        padded.extend(&der[2..37]);
+        padded.extend([0x02, 0x21, 0x00]);
+        padded.extend(&der[39..]);
+        assert_eq!(
+            decode_ecdsa_signature(&padded, 32),
+            Err(DerError::NonMinimalInteger)
+        );
+
+        assert_eq!(
+            decode_ecdsa_signature(&[0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x01], 32),
+            Err(DerError::ZeroInteger)
+        );
+        assert_eq!(
+            decode_ecdsa_signature(&[0x30, 0x80, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0, 0], 32),
+            Err(DerError::UnsupportedLength)
+        );
+        assert_eq!(
+            decode_ecdsa_signature(&der[..der.len() - 1], 32),
+            Err(DerError::UnexpectedEnd)
+        );
+    }
+}
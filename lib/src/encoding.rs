@@ -0,0 +1,211 @@
+//! Serde helpers that store byte fields compactly in human-readable formats.
+//!
+//! JSON gets a `0x`-prefixed hex string or a `base64:`-prefixed base64 string, and also still
+//! accepts the plain array of numbers the sxg-extension emits. Binary formats such as the bincode used for the zkVM stdin
+//! keep the plain byte sequence, so the guest sees exactly what it did before.
+
+use std::fmt;
+
+use base64::Engine;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serializer,
+};
+
+/// How byte fields are written out by [`crate::sxg::SXGInput::to_json`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl ByteEncoding {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            ByteEncoding::Hex => format!("0x{}", hex::encode(bytes)),
+            ByteEncoding::Base64 => format!(
+                "{}{}",
+                BASE64_PREFIX,
+                base64::prelude::BASE64_STANDARD.encode(bytes)
+            ),
+        }
+    }
+}
+
+/// Marks base64 strings, which could otherwise start with `0x` themselves.
+pub const BASE64_PREFIX: &str = "base64:";
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, String> {
+    base64::prelude::BASE64_STANDARD
+        .decode(value)
+        .map_err(|e| format!("invalid base64: {}", e))
+}
+
+/// Decodes a `0x`-prefixed hex string or a `base64:`-prefixed base64 string.
+///
+/// Files written before the prefix existed hold bare base64, which is still read. A bare base64
+/// string that starts with `0x` is only taken for hex when the rest is valid hex.
+pub fn decode_str(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(base64_value) = value.strip_prefix(BASE64_PREFIX) {
+        return decode_base64(base64_value);
+    }
+    match value.strip_prefix("0x") {
+        Some(hex_value) => hex::decode(hex_value)
+            .or_else(|e| decode_base64(value).map_err(|_| format!("invalid hex: {}", e))),
+        None => decode_base64(value),
+    }
+}
+
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&ByteEncoding::Hex.encode(bytes))
+    } else {
+        serializer.collect_seq(bytes)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(BytesVisitor)
+    } else {
+        Vec::<u8>::deserialize(deserializer)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a 0x-prefixed hex string, a base64:-prefixed base64 string or an array of bytes"
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<u8>, E> {
+        decode_str(value).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+/// The same encoding for `Option<Vec<u8>>` fields, with `None` as `null`.
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&Wrapper(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Ok(Option::<Owned>::deserialize(deserializer)?.map(|owned| owned.0))
+    }
+
+    struct Wrapper<'a>(&'a [u8]);
+
+    impl serde::Serialize for Wrapper<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, serializer)
+        }
+    }
+
+    struct Owned(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for Owned {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Owned)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{decode_str, ByteEncoding};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Bytes {
+        #[serde(with = "super")]
+        value: Vec<u8>,
+        #[serde(default, with = "super::option")]
+        optional: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn test_json_accepts_every_form() {
+        let expected = Bytes {
+            value: vec![0xde, 0xad, 0xbe, 0xef],
+            optional: None,
+        };
+        for json in [
+            r#"{"value": "0xdeadbeef"}"#,
+            r#"{"value": "base64:3q2+7w=="}"#,
+            r#"{"value": "3q2+7w=="}"#,
+            r#"{"value": [222, 173, 190, 239], "optional": null}"#,
+        ] {
+            assert_eq!(serde_json::from_str::<Bytes>(json).unwrap(), expected);
+        }
+
+        assert!(serde_json::from_str::<Bytes>(r#"{"value": "0xdeadbee"}"#).is_err());
+        assert!(serde_json::from_str::<Bytes>(r#"{"value": "not base64!"}"#).is_err());
+        assert!(serde_json::from_str::<Bytes>(r#"{"value": "base64:0xdeadbeef"}"#).is_err());
+    }
+
+    #[test]
+    fn test_base64_that_looks_like_hex() {
+        // Base64 of bytes starting 0xD3 0x1? begins with "0x".
+        let bytes = vec![0xd3, 0x10, 0x00, 0xff];
+        let bare = base64::Engine::encode(&base64::prelude::BASE64_STANDARD, &bytes);
+        assert!(bare.starts_with("0x"));
+
+        let encoded = ByteEncoding::Base64.encode(&bytes);
+        assert_eq!(encoded, format!("base64:{}", bare));
+        assert_eq!(decode_str(&encoded).unwrap(), bytes);
+        // Older files hold the bare form, which is not valid hex.
+        assert_eq!(decode_str(&bare).unwrap(), bytes);
+        assert_eq!(
+            decode_str(&ByteEncoding::Hex.encode(&bytes)).unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let bytes = Bytes {
+            value: vec![1, 2, 3],
+            optional: Some(vec![4, 5]),
+        };
+
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(json, r#"{"value":"0x010203","optional":"0x0405"}"#);
+        assert_eq!(serde_json::from_str::<Bytes>(&json).unwrap(), bytes);
+
+        // Binary formats keep the plain `Vec<u8>` layout.
+        let encoded = bincode::serialize(&bytes).unwrap();
+        let plain = bincode::serialize(&(vec![1u8, 2, 3], Some(vec![4u8, 5]))).unwrap();
+        assert_eq!(encoded, plain);
+        assert_eq!(bincode::deserialize::<Bytes>(&encoded).unwrap(), bytes);
+    }
+}
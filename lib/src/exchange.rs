@@ -0,0 +1,426 @@
+//! Parsing of raw `application/signed-exchange;v=b3` files, as served to crawlers and saved by
+//! browsers.
+//!
+//! An exchange is the magic string, the fallback (request) URL, the `Signature` header value,
+//! the signed response headers and the mi-sha256 encoded payload. The signed message an
+//! [`SXGInput`] carries as `final_payload` is rebuilt from these with
+//! [`Exchange::signed_message`].
+//!
+//! [`SXGInput`]: crate::sxg::SXGInput
+
+use std::fmt;
+
+use base64::Engine;
+
+use crate::message::SignedMessage;
+
+pub const MAGIC: &[u8] = b"sxg1-b3\0";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExchangeError {
+    BadMagic,
+    Truncated {
+        offset: usize,
+        expected: &'static str,
+    },
+    InvalidSignatureHeader {
+        offset: usize,
+        reason: &'static str,
+    },
+    MissingSignatureParam(&'static str),
+    InvalidPayload(&'static str),
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeError::BadMagic => write!(f, "not a b3 signed exchange (bad magic)"),
+            ExchangeError::Truncated { offset, expected } => {
+                write!(f, "exchange ends at byte {}: expected {}", offset, expected)
+            }
+            ExchangeError::InvalidSignatureHeader { offset, reason } => write!(
+                f,
+                "invalid Signature header at character {}: {}",
+                offset, reason
+            ),
+            ExchangeError::MissingSignatureParam(name) => {
+                write!(f, "Signature header has no `{}` parameter", name)
+            }
+            ExchangeError::InvalidPayload(reason) => {
+                write!(f, "invalid mi-sha256 payload: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange<'a> {
+    pub fallback_url: &'a [u8],
+    /// The `Signature` header value, see [`Exchange::signatures`].
+    pub signature: &'a [u8],
+    /// A canonical CBOR map of the response headers.
+    pub signed_headers: &'a [u8],
+    /// The response body in the mi-sha256 encoding, see [`decode_mi_sha256`].
+    pub payload: &'a [u8],
+}
+
+/// One signature from the `Signature` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureParams {
+    pub label: String,
+    /// The DER-encoded `ECDSA-Sig-Value`.
+    pub sig: Vec<u8>,
+    pub integrity: String,
+    pub cert_url: Option<String>,
+    pub cert_sha256: Option<Vec<u8>>,
+    pub validity_url: String,
+    pub date: u64,
+    pub expires: u64,
+}
+
+fn take<'a>(
+    bytes: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+    expected: &'static str,
+) -> Result<&'a [u8], ExchangeError> {
+    let error = ExchangeError::Truncated {
+        offset: bytes.len(),
+        expected,
+    };
+    let end = offset.checked_add(len).ok_or(error.clone())?;
+    let taken = bytes.get(*offset..end).ok_or(error)?;
+    *offset = end;
+    Ok(taken)
+}
+
+fn be_length(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, b| acc << 8 | usize::from(*b))
+}
+
+impl<'a> Exchange<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ExchangeError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(ExchangeError::BadMagic);
+        }
+        let mut offset = MAGIC.len();
+
+        let len = be_length(take(bytes, &mut offset, 2, "the fallback URL length")?);
+        let fallback_url = take(bytes, &mut offset, len, "the fallback URL")?;
+        let signature_len = be_length(take(bytes, &mut offset, 3, "the signature length")?);
+        let headers_len = be_length(take(bytes, &mut offset, 3, "the headers length")?);
+        let signature = take(bytes, &mut offset, signature_len, "the Signature header")?;
+        let signed_headers = take(bytes, &mut offset, headers_len, "the signed headers")?;
+
+        Ok(Exchange {
+            fallback_url,
+            signature,
+            signed_headers,
+            payload: &bytes[offset..],
+        })
+    }
+
+    /// Parses the `Signature` header into its signatures, in header order.
+    pub fn signatures(&self) -> Result<Vec<SignatureParams>, ExchangeError> {
+        parse_signature_header(self.signature)
+    }
+
+    /// Rebuilds the message that `params` signs.
+    pub fn signed_message(&self, params: &SignatureParams) -> Vec<u8> {
+        SignedMessage {
+            cert_sha256: params.cert_sha256.as_deref(),
+            validity_url: params.validity_url.as_bytes(),
+            date: params.date,
+            expires: params.expires,
+            request_url: self.fallback_url,
+            response_headers: self.signed_headers,
+        }
+        .to_bytes()
+    }
+}
+
+/// Splits an mi-sha256 encoded body into its record size and the decoded payload.
+///
+/// The integrity proofs interleaved between records are dropped; the digest of the decoded
+/// payload is what the signed `digest` header commits to.
+pub fn decode_mi_sha256(encoded: &[u8]) -> Result<(usize, Vec<u8>), ExchangeError> {
+    let (record_size, mut rest) = encoded
+        .split_first_chunk::<8>()
+        .ok_or(ExchangeError::InvalidPayload("missing the record size"))?;
+    let record_size = usize::try_from(u64::from_be_bytes(*record_size))
+        .map_err(|_| ExchangeError::InvalidPayload("record size is too large"))?;
+    if record_size == 0 && !rest.is_empty() {
+        return Err(ExchangeError::InvalidPayload("record size is zero"));
+    }
+
+    let mut payload = Vec::with_capacity(rest.len());
+    while rest.len() > record_size {
+        let (record, proof_and_rest) = rest.split_at(record_size);
+        payload.extend_from_slice(record);
+        rest = proof_and_rest
+            .get(32..)
+            .filter(|rest| !rest.is_empty())
+            .ok_or(ExchangeError::InvalidPayload(
+                "a record does not follow every proof",
+            ))?;
+    }
+    payload.extend_from_slice(rest);
+
+    Ok((record_size, payload))
+}
+
+/// A parameter value in the structured `Signature` header.
+enum Item {
+    String(String),
+    Bytes(Vec<u8>),
+    Integer(u64),
+}
+
+struct HeaderParser<'a> {
+    header: &'a [u8],
+    offset: usize,
+}
+
+impl HeaderParser<'_> {
+    fn error(&self, reason: &'static str) -> ExchangeError {
+        ExchangeError::InvalidSignatureHeader {
+            offset: self.offset,
+            reason,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.header.get(self.offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.offset += 1;
+        }
+    }
+
+    fn token(&mut self) -> Result<String, ExchangeError> {
+        let start = self.offset;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || b"-_.*".contains(&c)) {
+            self.offset += 1;
+        }
+        if start == self.offset {
+            return Err(self.error("expected a token"));
+        }
+        Ok(String::from_utf8_lossy(&self.header[start..self.offset]).into_owned())
+    }
+
+    fn item(&mut self) -> Result<Item, ExchangeError> {
+        match self.peek() {
+            Some(b'"') => {
+                self.offset += 1;
+                let mut value = String::new();
+                loop {
+                    match self.peek() {
+                        None => return Err(self.error("unterminated string")),
+                        Some(b'"') => break,
+                        Some(b'\\') => {
+                            self.offset += 1;
+                            let escaped = self.peek().ok_or(self.error("unterminated string"))?;
+                            value.push(char::from(escaped));
+                        }
+                        Some(c) => value.push(char::from(c)),
+                    }
+                    self.offset += 1;
+                }
+                self.offset += 1;
+                Ok(Item::String(value))
+            }
+            Some(b'*') => {
+                self.offset += 1;
+                let start = self.offset;
+                while matches!(self.peek(), Some(c) if c != b'*') {
+                    self.offset += 1;
+                }
+                if self.peek().is_none() {
+                    return Err(self.error("unterminated byte sequence"));
+                }
+                let bytes = base64::prelude::BASE64_STANDARD
+                    .decode(&self.header[start..self.offset])
+                    .map_err(|_| self.error("invalid base64 in byte sequence"))?;
+                self.offset += 1;
+                Ok(Item::Bytes(bytes))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.offset;
+                while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                    self.offset += 1;
+                }
+                std::str::from_utf8(&self.header[start..self.offset])
+                    .expect("ASCII digits")
+                    .parse()
+                    .map(Item::Integer)
+                    .map_err(|_| self.error("integer is too large"))
+            }
+            _ => Err(self.error("expected a string, byte sequence or integer")),
+        }
+    }
+}
+
+fn parse_signature_header(header: &[u8]) -> Result<Vec<SignatureParams>, ExchangeError> {
+    let mut parser = HeaderParser { header, offset: 0 };
+    let mut signatures = Vec::new();
+
+    loop {
+        parser.skip_whitespace();
+        let label = parser.token()?;
+        let mut params = Vec::new();
+        parser.skip_whitespace();
+        while parser.peek() == Some(b';') {
+            parser.offset += 1;
+            parser.skip_whitespace();
+            let name = parser.token()?;
+            if parser.peek() != Some(b'=') {
+                return Err(parser.error("expected `=` after a parameter name"));
+            }
+            parser.offset += 1;
+            params.push((name, parser.item()?));
+            parser.skip_whitespace();
+        }
+        signatures.push(signature_params(label, params)?);
+
+        match parser.peek() {
+            None => return Ok(signatures),
+            Some(b',') => parser.offset += 1,
+            Some(_) => return Err(parser.error("expected `;`, `,` or the end of the header")),
+        }
+    }
+}
+
+fn signature_params(
+    label: String,
+    params: Vec<(String, Item)>,
+) -> Result<SignatureParams, ExchangeError> {
+    let mut sig = None;
+    let mut integrity = None;
+    let mut cert_url = None;
+    let mut cert_sha256 = None;
+    let mut validity_url = None;
+    let mut date = None;
+    let mut expires = None;
+
+    for (name, item) in params {
+        match (name.as_str(), item) {
+            ("sig", Item::Bytes(v)) => sig = Some(v),
+            ("integrity", Item::String(v)) => integrity = Some(v),
+            ("cert-url", Item::String(v)) => cert_url = Some(v),
+            ("cert-sha256", Item::Bytes(v)) => cert_sha256 = Some(v),
+            ("validity-url", Item::String(v)) => validity_url = Some(v),
+            ("date", Item::Integer(v)) => date = Some(v),
+            ("expires", Item::Integer(v)) => expires = Some(v),
+            // Unknown parameters, and known ones of the wrong type, are ignored.
+            _ => {}
+        }
+    }
+
+    Ok(SignatureParams {
+        label,
+        sig: sig.ok_or(ExchangeError::MissingSignatureParam("sig"))?,
+        integrity: integrity.ok_or(ExchangeError::MissingSignatureParam("integrity"))?,
+        cert_url,
+        cert_sha256,
+        validity_url: validity_url.ok_or(ExchangeError::MissingSignatureParam("validity-url"))?,
+        date: date.ok_or(ExchangeError::MissingSignatureParam("date"))?,
+        expires: expires.ok_or(ExchangeError::MissingSignatureParam("expires"))?,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{sxg::mi_sha256_digest_with_record_size, test_case_1::FINAL_PAYLOAD_1};
+
+    /// Encodes `payload` as an mi-sha256 body with the integrity proofs zeroed out.
+    pub(crate) fn encode_mi_sha256(payload: &[u8], record_size: usize) -> Vec<u8> {
+        let mut encoded = (record_size as u64).to_be_bytes().to_vec();
+        for (i, record) in payload.chunks(record_size).enumerate() {
+            if i > 0 {
+                encoded.extend([0; 32]);
+            }
+            encoded.extend(record);
+        }
+        encoded
+    }
+
+    /// Wraps the signed message of test case 1 back into a raw exchange signed with `sig`.
+    pub(crate) fn exchange_testcase_1_signed(payload: &[u8], sig: &[u8]) -> Vec<u8> {
+        let message = SignedMessage::parse(FINAL_PAYLOAD_1).unwrap();
+        let b64 = |bytes: &[u8]| base64::prelude::BASE64_STANDARD.encode(bytes);
+        let signature = format!(
+            "label; sig=*{}*; integrity=\"digest/mi-sha256-03\"; cert-url=\"https://blog.crema.sh/cert\"; cert-sha256=*{}*; validity-url=\"{}\"; date={}; expires={}",
+            b64(sig),
+            b64(message.cert_sha256.unwrap()),
+            String::from_utf8_lossy(message.validity_url),
+            message.date,
+            message.expires,
+        );
+
+        let mut exchange = MAGIC.to_vec();
+        exchange.extend((message.request_url.len() as u16).to_be_bytes());
+        exchange.extend(message.request_url);
+        exchange.extend(&(signature.len() as u32).to_be_bytes()[1..]);
+        exchange.extend(&(message.response_headers.len() as u32).to_be_bytes()[1..]);
+        exchange.extend(signature.as_bytes());
+        exchange.extend(message.response_headers);
+        exchange.extend(payload);
+        exchange
+    }
+
+    fn exchange_testcase_1(payload: &[u8]) -> Vec<u8> {
+        exchange_testcase_1_signed(payload, &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01])
+    }
+
+    #[test]
+    fn test_parse_exchange() {
+        let body = b"<html>0123456789</html>";
+        let bytes = exchange_testcase_1(&encode_mi_sha256(body, 4));
+        let exchange = Exchange::parse(&bytes).unwrap();
+        assert_eq!(exchange.fallback_url, b"https://blog.crema.sh/");
+
+        let signatures = exchange.signatures().unwrap();
+        assert_eq!(signatures.len(), 1);
+        let params = &signatures[0];
+        assert_eq!(params.label, "label");
+        assert_eq!(params.integrity, "digest/mi-sha256-03");
+        assert_eq!(
+            params.cert_url.as_deref(),
+            Some("https://blog.crema.sh/cert")
+        );
+        assert_eq!(exchange.signed_message(params), FINAL_PAYLOAD_1);
+
+        let (record_size, payload) = decode_mi_sha256(exchange.payload).unwrap();
+        assert_eq!((record_size, payload.as_slice()), (4, &body[..]));
+        assert_eq!(
+            mi_sha256_digest_with_record_size(&payload, 4),
+            mi_sha256_digest_with_record_size(body, 4)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_exchanges() {
+        let bytes = exchange_testcase_1(&encode_mi_sha256(b"body", 16));
+        assert_eq!(Exchange::parse(&bytes[1..]), Err(ExchangeError::BadMagic));
+        assert!(matches!(
+            Exchange::parse(&bytes[..40]),
+            Err(ExchangeError::Truncated { .. })
+        ));
+
+        assert!(parse_signature_header(b"label; sig=*AAAA*").is_err());
+        assert!(matches!(
+            parse_signature_header(b"label; sig=*AAAA; date=1"),
+            Err(ExchangeError::InvalidSignatureHeader { .. })
+        ));
+
+        let mut trailing_proof = encode_mi_sha256(b"12345678", 4);
+        trailing_proof.extend([0; 32]);
+        assert!(decode_mi_sha256(&trailing_proof).is_err());
+        assert_eq!(decode_mi_sha256(&0u64.to_be_bytes()).unwrap().1, b"");
+    }
+}
@@ -1,19 +1,98 @@
+#[cfg(not(target_os = "zkvm"))]
+pub mod builder;
+pub mod der;
+pub mod encoding;
+#[cfg(not(target_os = "zkvm"))]
+pub mod exchange;
+pub mod message;
+#[cfg(not(target_os = "zkvm"))]
+pub mod report;
+pub mod schema;
 pub mod sxg;
 pub mod test_case_1;
 pub mod test_case_2;
 
-use alloy_sol_types::sol;
-use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use alloy_sol_types::{sol, SolType};
+use p256::{
+    ecdsa::{signature::Verifier, Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+/// Runs `$body` between SP1 `cycle-tracker-report` markers, so the cycles it takes in the guest
+/// are added up under `$stage` in the execution report. On the host it only runs `$body`.
+#[macro_export]
+macro_rules! track_cycles {
+    ($stage:literal, $body:expr) => {{
+        #[cfg(target_os = "zkvm")]
+        println!(concat!("cycle-tracker-report-start: ", $stage));
+        let result = $body;
+        #[cfg(target_os = "zkvm")]
+        println!(concat!("cycle-tracker-report-end: ", $stage));
+        result
+    }};
+}
+
+/// The stages the guests track with [`track_cycles!`], in the order they run.
+pub const CYCLE_STAGES: [&str; 7] = [
+    "deserialize",
+    "disclosure",
+    "mi_sha256",
+    "base64",
+    "signature",
+    "public_values",
+    "abi_encode",
+];
+
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
     struct PublicValuesStruct {
         uint32 result;
         uint8[] data_to_verify;
+        bytes32 signature_hash;
+        bytes public_key;
+        string url;
+        /// The [`LowSPolicy`] the signature was checked under, as its `u8` discriminant.
+        uint8 low_s_policy;
+    }
+
+    /// The public values of the batch program, which verifies several exchanges in one run.
+    struct BatchPublicValuesStruct {
+        PublicValuesStruct[] exchanges;
+        bytes32 combined_hash;
+    }
+
+    /// The public values of the aggregation program, which verifies existing SXG proofs.
+    struct AggregationPublicValuesStruct {
+        bytes32 sxg_vkey_hash;
+        bytes32[] public_values_digests;
     }
 }
 
+/// The big-endian bytes of a verifying key hash given as `vk.hash_u32()` words, as committed in
+/// [`AggregationPublicValuesStruct::sxg_vkey_hash`].
+pub fn vkey_hash_bytes(words: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+/// Chains the exchanges of a batch into one hash: `sha256(d_1 || ... || d_n)`, where `d_i` is the
+/// SHA-256 of the ABI-encoded `exchanges[i]`.
+///
+/// Each `d_i` is the public values digest the single-exchange program would have committed for the
+/// same input, so the hash can also be recomputed from individual proofs.
+pub fn combined_hash(exchanges: &[PublicValuesStruct]) -> [u8; 32] {
+    let digests: Vec<u8> = exchanges
+        .iter()
+        .flat_map(|exchange| sha256_hash(&PublicValuesStruct::abi_encode(exchange)))
+        .collect();
+    sha256_hash(&digests)
+}
+
 pub fn sha256_hash(bytes: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(bytes);
@@ -55,6 +134,158 @@ pub fn verify_ecdsa_p256_r_s(
     Ok(verifying_key.verify(message, &signature).is_ok())
 }
 
+pub fn verify_ecdsa_p384_r_s(
+    message: &[u8],
+    r: &[u8; 48],
+    s: &[u8; 48],
+    px: &[u8; 48],
+    py: &[u8; 48],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut signature_bytes = [0u8; 96];
+    signature_bytes[..48].copy_from_slice(r);
+    signature_bytes[48..].copy_from_slice(s);
+    let signature = p384::ecdsa::Signature::from_slice(&signature_bytes)?;
+
+    let mut public_key_bytes = [4u8; 97];
+    public_key_bytes[1..49].copy_from_slice(px);
+    public_key_bytes[49..].copy_from_slice(py);
+
+    let verifying_key = p384::ecdsa::VerifyingKey::from_sec1_bytes(&public_key_bytes)?;
+
+    Ok(verifying_key.verify(message, &signature).is_ok())
+}
+
+/// The SXG signature algorithms, named after their `cert-sha256`/TLS identifiers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgorithm {
+    #[default]
+    #[serde(rename = "ecdsa_secp256r1_sha256")]
+    EcdsaP256Sha256,
+    #[serde(rename = "ecdsa_secp384r1_sha384")]
+    EcdsaP384Sha384,
+}
+
+impl SignatureAlgorithm {
+    /// The byte length of a scalar or coordinate on the curve.
+    pub fn field_size(&self) -> usize {
+        match self {
+            SignatureAlgorithm::EcdsaP256Sha256 => 32,
+            SignatureAlgorithm::EcdsaP384Sha384 => 48,
+        }
+    }
+
+    /// Verifies `(r, s)` over `message` against the affine public key `(px, py)`.
+    pub fn verify_r_s(
+        &self,
+        message: &[u8],
+        r: &[u8],
+        s: &[u8],
+        px: &[u8],
+        py: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let size = self.field_size();
+        if [r, s, px, py].iter().any(|v| v.len() != size) {
+            return Err(
+                format!("{:?} expects {}-byte signature and key fields", self, size).into(),
+            );
+        }
+
+        match self {
+            SignatureAlgorithm::EcdsaP256Sha256 => verify_ecdsa_p256_r_s(
+                message,
+                r.try_into()?,
+                s.try_into()?,
+                px.try_into()?,
+                py.try_into()?,
+            ),
+            SignatureAlgorithm::EcdsaP384Sha384 => verify_ecdsa_p384_r_s(
+                message,
+                r.try_into()?,
+                s.try_into()?,
+                px.try_into()?,
+                py.try_into()?,
+            ),
+        }
+    }
+
+    /// Parses a compressed or uncompressed SEC1 point, or a SubjectPublicKeyInfo DER structure,
+    /// into the canonical uncompressed SEC1 encoding `04 || x || y`.
+    pub fn canonical_public_key(&self, key: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        // SEC1 points start with 0x02, 0x03 or 0x04; SPKI is a DER SEQUENCE.
+        let is_spki = key.first() == Some(&0x30);
+        let point = match self {
+            SignatureAlgorithm::EcdsaP256Sha256 => {
+                let verifying_key = if is_spki {
+                    VerifyingKey::from_public_key_der(key)?
+                } else {
+                    VerifyingKey::from_sec1_bytes(key)?
+                };
+                verifying_key.to_encoded_point(false).as_bytes().to_vec()
+            }
+            SignatureAlgorithm::EcdsaP384Sha384 => {
+                let verifying_key = if is_spki {
+                    p384::ecdsa::VerifyingKey::from_public_key_der(key)?
+                } else {
+                    p384::ecdsa::VerifyingKey::from_sec1_bytes(key)?
+                };
+                verifying_key.to_encoded_point(false).as_bytes().to_vec()
+            }
+        };
+        Ok(point)
+    }
+
+    /// Returns `n - s` when `s` is in the upper half of the curve order, `None` when it is
+    /// already low.
+    pub fn normalize_s(
+        &self,
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let signature_bytes = [r, s].concat();
+        let normalized = match self {
+            SignatureAlgorithm::EcdsaP256Sha256 => Signature::from_slice(&signature_bytes)?
+                .normalize_s()
+                .map(|signature| signature.s().to_bytes().to_vec()),
+            SignatureAlgorithm::EcdsaP384Sha384 => {
+                p384::ecdsa::Signature::from_slice(&signature_bytes)?
+                    .normalize_s()
+                    .map(|signature| signature.s().to_bytes().to_vec())
+            }
+        };
+        Ok(normalized)
+    }
+}
+
+/// How to treat the malleable `(r, n - s)` twin of a valid signature.
+///
+/// The policy is committed as [`PublicValuesStruct::low_s_policy`], so a verifier can insist on
+/// the one it needs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum LowSPolicy {
+    /// Accept either `s`, as plain ECDSA does.
+    #[default]
+    Accept = 0,
+    /// Reject signatures whose `s` is in the upper half of the curve order.
+    Reject = 1,
+    /// Accept either `s`, but commit a hash of the low-S form so both map to the same value.
+    Normalize = 2,
+}
+
+impl LowSPolicy {
+    /// The policy committed as `low_s_policy`, if the value names one.
+    pub fn from_committed(value: u8) -> Option<Self> {
+        [
+            LowSPolicy::Accept,
+            LowSPolicy::Reject,
+            LowSPolicy::Normalize,
+        ]
+        .into_iter()
+        .find(|policy| *policy as u8 == value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_case_1::FINAL_PAYLOAD_1;
@@ -118,4 +349,64 @@ mod tests {
         let result1 = verify_ecdsa_p256_r_s(message1, &r, &s, &px, &py).unwrap();
         assert!(result1);
     }
+
+    #[test]
+    fn test_verify_ecdsa_p384_r_s() {
+        use p384::ecdsa::{signature::Signer, SigningKey};
+
+        let signing_key = SigningKey::from_slice(&[7u8; 48]).unwrap();
+        let signature: p384::ecdsa::Signature = signing_key.sign(FINAL_PAYLOAD_1);
+        let point = signing_key.verifying_key().to_encoded_point(false);
+
+        let (r, s) = signature.split_bytes();
+        let r: [u8; 48] = r.into();
+        let s: [u8; 48] = s.into();
+        let px: [u8; 48] = (*point.x().unwrap()).into();
+        let py: [u8; 48] = (*point.y().unwrap()).into();
+
+        assert!(verify_ecdsa_p384_r_s(FINAL_PAYLOAD_1, &r, &s, &px, &py).unwrap());
+        assert!(!verify_ecdsa_p384_r_s(FINAL_PAYLOAD_2, &r, &s, &px, &py).unwrap());
+
+        let algorithm = SignatureAlgorithm::EcdsaP384Sha384;
+        assert!(algorithm
+            .verify_r_s(FINAL_PAYLOAD_1, &r, &s, &px, &py)
+            .unwrap());
+        assert!(SignatureAlgorithm::EcdsaP256Sha256
+            .verify_r_s(FINAL_PAYLOAD_1, &r, &s, &px, &py)
+            .is_err());
+    }
+
+    #[test]
+    fn test_canonical_public_key() {
+        let px = "45E3943B0705F9EF69B53A4EFB8C668E6A9F90124E9BCF917662CFADEA56C0C1";
+        let py = "F3703834F92F6FE70A004BA4098D079BFB5F927E042991EFD5A1572E8F9D39D6";
+        let uncompressed = hex::decode(format!("04{}{}", px, py)).unwrap();
+        // `py` is even, hence the 0x02 prefix.
+        let compressed = hex::decode(format!("02{}", px)).unwrap();
+        let spki = hex::decode(format!(
+            "3059301306072a8648ce3d020106082a8648ce3d03010703420004{}{}",
+            px, py
+        ))
+        .unwrap();
+
+        let algorithm = SignatureAlgorithm::EcdsaP256Sha256;
+        for key in [&uncompressed, &compressed, &spki] {
+            assert_eq!(algorithm.canonical_public_key(key).unwrap(), uncompressed);
+        }
+
+        let mut off_curve = uncompressed.clone();
+        off_curve[64] ^= 1;
+        assert!(algorithm.canonical_public_key(&off_curve).is_err());
+        assert!(SignatureAlgorithm::EcdsaP384Sha384
+            .canonical_public_key(&spki)
+            .is_err());
+    }
+
+    #[test]
+    fn test_vkey_hash_bytes() {
+        let words = [0x0011_2233, 0x4455_6677, 0, 0, 0, 0, 0, 0xdead_beef];
+        let bytes = vkey_hash_bytes(&words);
+        assert_eq!(bytes[..8], hex::decode("0011223344556677").unwrap());
+        assert_eq!(bytes[28..], [0xde, 0xad, 0xbe, 0xef]);
+    }
 }
@@ -0,0 +1,305 @@
+//! Parsing of the message an SXG signature covers, the `final_payload` of an [`SXGInput`].
+//!
+//! For `b3` exchanges the signed message is 64 spaces, the context string, a zero byte, the
+//! certificate hash, the validity URL, the date and expiry, the request URL and the CBOR-encoded
+//! response headers. Variable-length fields carry an 8-byte big-endian length prefix.
+//!
+//! [`SXGInput`]: crate::sxg::SXGInput
+
+use std::fmt;
+
+pub const CONTEXT: &[u8] = b"HTTP Exchange 1 b3";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage<'a> {
+    pub cert_sha256: Option<&'a [u8]>,
+    pub validity_url: &'a [u8],
+    /// Seconds since the Unix epoch.
+    pub date: u64,
+    /// Seconds since the Unix epoch.
+    pub expires: u64,
+    pub request_url: &'a [u8],
+    /// A canonical CBOR map from header names to values, see [`SignedMessage::headers`].
+    pub response_headers: &'a [u8],
+}
+
+/// A response header as a `(name, value)` pair.
+pub type Header<'a> = (&'a [u8], &'a [u8]);
+
+/// A malformed signed message, with the offset at which parsing stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageError {
+    pub offset: usize,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "malformed signed message at byte {}: expected {}",
+            self.offset, self.expected
+        )
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+struct Reader<'a> {
+    message: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, expected: &'static str) -> Result<&'a [u8], MessageError> {
+        let error = MessageError {
+            offset: self.offset,
+            expected,
+        };
+        let end = self.offset.checked_add(len).ok_or(error.clone())?;
+        let bytes = self.message.get(self.offset..end).ok_or(error)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self, expected: &'static str) -> Result<u64, MessageError> {
+        let bytes = self.take(8, expected)?;
+        Ok(u64::from_be_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    fn length_prefixed(&mut self, expected: &'static str) -> Result<&'a [u8], MessageError> {
+        let offset = self.offset;
+        let len = self.u64(expected)?;
+        let len = usize::try_from(len).map_err(|_| MessageError { offset, expected })?;
+        self.take(len, expected)
+    }
+
+    /// Reads the head of a CBOR data item of major type `major` and returns its argument.
+    fn cbor_head(&mut self, major: u8, expected: &'static str) -> Result<usize, MessageError> {
+        let offset = self.offset;
+        let error = MessageError { offset, expected };
+        let initial = self.take(1, expected)?[0];
+        if initial >> 5 != major {
+            return Err(error);
+        }
+        let argument = match initial & 0x1f {
+            n @ 0..=23 => u64::from(n),
+            n @ 24..=27 => self
+                .take(1 << (n - 24), expected)?
+                .iter()
+                .fold(0, |acc, b| acc << 8 | u64::from(*b)),
+            _ => return Err(error),
+        };
+        usize::try_from(argument).map_err(|_| error)
+    }
+
+    fn expect(&mut self, bytes: &[u8], expected: &'static str) -> Result<(), MessageError> {
+        let offset = self.offset;
+        if self.take(bytes.len(), expected)? != bytes {
+            return Err(MessageError { offset, expected });
+        }
+        Ok(())
+    }
+}
+
+impl<'a> SignedMessage<'a> {
+    pub fn parse(message: &'a [u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader { message, offset: 0 };
+
+        reader.expect(&[b' '; 64], "64 spaces")?;
+        reader.expect(CONTEXT, "the \"HTTP Exchange 1 b3\" context string")?;
+        reader.expect(&[0], "a zero byte after the context string")?;
+
+        let cert_sha256 = match reader.take(1, "the cert-sha256 length")?[0] {
+            0 => None,
+            32 => Some(reader.take(32, "a 32-byte cert-sha256")?),
+            _ => {
+                return Err(MessageError {
+                    offset: reader.offset - 1,
+                    expected: "a cert-sha256 length of 0 or 32",
+                })
+            }
+        };
+
+        let validity_url = reader.length_prefixed("the validity URL")?;
+        let date = reader.u64("the signature date")?;
+        let expires = reader.u64("the signature expiry")?;
+        let request_url = reader.length_prefixed("the request URL")?;
+        let response_headers = reader.length_prefixed("the response headers")?;
+
+        if reader.offset != message.len() {
+            return Err(MessageError {
+                offset: reader.offset,
+                expected: "the end of the message after the response headers",
+            });
+        }
+
+        Ok(SignedMessage {
+            cert_sha256,
+            validity_url,
+            date,
+            expires,
+            request_url,
+            response_headers,
+        })
+    }
+
+    /// Serializes the message back into the bytes the signature covers.
+    ///
+    /// `cert_sha256`, when present, must be 32 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut message = vec![b' '; 64];
+        message.extend_from_slice(CONTEXT);
+        message.push(0);
+        match self.cert_sha256 {
+            Some(cert_sha256) => {
+                message.push(cert_sha256.len() as u8);
+                message.extend_from_slice(cert_sha256);
+            }
+            None => message.push(0),
+        }
+        for (field, is_length_prefixed) in [
+            (self.validity_url, true),
+            (&self.date.to_be_bytes()[..], false),
+            (&self.expires.to_be_bytes()[..], false),
+            (self.request_url, true),
+            (self.response_headers, true),
+        ] {
+            if is_length_prefixed {
+                message.extend_from_slice(&(field.len() as u64).to_be_bytes());
+            }
+            message.extend_from_slice(field);
+        }
+        message
+    }
+
+    /// Byte offset of the response headers within the serialized message.
+    pub fn response_headers_offset(&self) -> usize {
+        64 + CONTEXT.len()
+            + 2
+            + self.cert_sha256.map_or(0, <[u8]>::len)
+            + 8
+            + self.validity_url.len()
+            + 16
+            + 8
+            + self.request_url.len()
+            + 8
+    }
+
+    /// Decodes the response headers into `(name, value)` pairs, in their signed order.
+    ///
+    /// Error offsets are relative to the start of the message.
+    pub fn headers(&self) -> Result<Vec<Header<'a>>, MessageError> {
+        let mut reader = Reader {
+            message: self.response_headers,
+            offset: 0,
+        };
+        let in_message = |e: MessageError| MessageError {
+            offset: e.offset + self.response_headers_offset(),
+            ..e
+        };
+
+        let len = reader
+            .cbor_head(5, "a CBOR map of response headers")
+            .map_err(in_message)?;
+        let mut headers = Vec::with_capacity(len.min(self.response_headers.len()));
+        for _ in 0..len {
+            let name_len = reader
+                .cbor_head(2, "a CBOR byte string header name")
+                .map_err(in_message)?;
+            let name = reader.take(name_len, "a header name").map_err(in_message)?;
+            let value_len = reader
+                .cbor_head(2, "a CBOR byte string header value")
+                .map_err(in_message)?;
+            let value = reader
+                .take(value_len, "a header value")
+                .map_err(in_message)?;
+            headers.push((name, value));
+        }
+
+        if reader.offset != self.response_headers.len() {
+            return Err(in_message(MessageError {
+                offset: reader.offset,
+                expected: "the end of the response headers",
+            }));
+        }
+        Ok(headers)
+    }
+
+    /// The value of the response header `name`, which must be lowercase.
+    pub fn header(&self, name: &str) -> Result<Option<&'a [u8]>, MessageError> {
+        Ok(self
+            .headers()?
+            .into_iter()
+            .find(|(header, _)| *header == name.as_bytes())
+            .map(|(_, value)| value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sxg::mi_sha256_digest,
+        test_case_1::{FINAL_PAYLOAD_1, PAYLOAD_1},
+    };
+
+    #[test]
+    fn test_parse_signed_message() {
+        let message = SignedMessage::parse(FINAL_PAYLOAD_1).unwrap();
+        assert_eq!(message.request_url, b"https://blog.crema.sh/");
+        assert_eq!(
+            message.validity_url,
+            b"https://blog.crema.sh/cdn-fpw/sxg/valid.msg.validity"
+        );
+        assert_eq!(message.cert_sha256.map(<[u8]>::len), Some(32));
+        assert!(message.date < message.expires);
+        assert_eq!(
+            message.response_headers_offset() + message.response_headers.len(),
+            FINAL_PAYLOAD_1.len()
+        );
+        assert_eq!(message.to_bytes(), FINAL_PAYLOAD_1);
+    }
+
+    #[test]
+    fn test_headers() {
+        let message = SignedMessage::parse(FINAL_PAYLOAD_1).unwrap();
+        let headers = message.headers().unwrap();
+        assert!(headers.iter().any(|(name, _)| *name == b":status"));
+
+        let digest = message.header("digest").unwrap().unwrap();
+        assert_eq!(digest, mi_sha256_digest(PAYLOAD_1));
+        assert_eq!(message.header("x-missing").unwrap(), None);
+
+        let mut truncated = FINAL_PAYLOAD_1.to_vec();
+        truncated.truncate(truncated.len() - 1);
+        let len_offset = message.response_headers_offset() - 8;
+        let len = message.response_headers.len() as u64 - 1;
+        truncated[len_offset..len_offset + 8].copy_from_slice(&len.to_be_bytes());
+        let error = SignedMessage::parse(&truncated)
+            .unwrap()
+            .headers()
+            .unwrap_err();
+        assert_eq!(error.expected, "a header value");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_messages() {
+        let truncated = &FINAL_PAYLOAD_1[..FINAL_PAYLOAD_1.len() - 1];
+        assert_eq!(
+            SignedMessage::parse(truncated).unwrap_err().expected,
+            "the response headers"
+        );
+
+        let mut trailing = FINAL_PAYLOAD_1.to_vec();
+        trailing.push(0);
+        assert_eq!(
+            SignedMessage::parse(&trailing).unwrap_err().offset,
+            FINAL_PAYLOAD_1.len()
+        );
+
+        let mut wrong_context = FINAL_PAYLOAD_1.to_vec();
+        wrong_context[81] = b'2';
+        assert_eq!(SignedMessage::parse(&wrong_context).unwrap_err().offset, 64);
+    }
+}
@@ -0,0 +1,419 @@
+//! A diagnostic version of [`SXGInput::verify`] that reports every check instead of stopping at
+//! the first failure.
+//!
+//! Byte mismatches carry both sides, the offset of the first differing byte and a window of the
+//! surrounding bytes, so a broken input can be fixed without re-deriving the offsets by hand.
+
+use std::{fmt::Write, ops::Range};
+
+use serde::Serialize;
+
+use crate::{builder::find_all, encoding, sxg::mi_sha256_digest, sxg::SXGInput, LowSPolicy};
+
+/// Bytes shown on each side of the first difference in [`Mismatch::window`].
+pub const WINDOW_CONTEXT: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not run because a check it depends on failed.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatch: Option<Mismatch>,
+    /// A likely fix, when one can be found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// Bytes found in an input field where other bytes were expected.
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    /// The input field the bytes were read from.
+    pub field: &'static str,
+    /// Where the compared range starts in `field`.
+    pub offset: usize,
+    #[serde(with = "encoding")]
+    pub expected: Vec<u8>,
+    /// The bytes at `offset`, shorter than `expected` if `field` ends first.
+    #[serde(with = "encoding")]
+    pub actual: Vec<u8>,
+    /// Offset in `field` of the first byte that differs.
+    pub first_difference: usize,
+    /// Where `window` starts in `field`.
+    pub window_offset: usize,
+    /// The bytes of `field` around `first_difference`.
+    #[serde(with = "encoding")]
+    pub window: Vec<u8>,
+}
+
+impl Mismatch {
+    fn new(field: &'static str, haystack: &[u8], offset: usize, expected: &[u8]) -> Self {
+        let clamp = |i: usize| i.min(haystack.len());
+        let actual = &haystack[clamp(offset)..clamp(offset.saturating_add(expected.len()))];
+        let first_difference = offset
+            + expected
+                .iter()
+                .zip(actual)
+                .position(|(e, a)| e != a)
+                .unwrap_or(actual.len());
+        let window_offset = clamp(first_difference.saturating_sub(WINDOW_CONTEXT));
+
+        Mismatch {
+            field,
+            offset,
+            expected: expected.to_vec(),
+            actual: actual.to_vec(),
+            first_difference,
+            window_offset,
+            window: haystack[window_offset..clamp(first_difference.saturating_add(WINDOW_CONTEXT))]
+                .to_vec(),
+        }
+    }
+
+    /// Renders `window` as a hexdump, with the first differing byte in brackets.
+    pub fn hexdump(&self) -> String {
+        hexdump(
+            &self.window,
+            self.window_offset,
+            Some(self.first_difference),
+        )
+    }
+}
+
+/// Renders `bytes`, which start at `offset` in their buffer, as 16-byte hexdump lines. The byte
+/// at `highlight`, if it is in range, is shown in brackets.
+pub fn hexdump(bytes: &[u8], offset: usize, highlight: Option<usize>) -> String {
+    let mut out = String::new();
+    let aligned = offset - offset % 16;
+    let end = offset + bytes.len();
+
+    for line in (aligned..end).step_by(16) {
+        let _ = write!(out, "{:08x} ", line);
+        let mut ascii = String::new();
+        for i in line..line + 16 {
+            let byte = i.checked_sub(offset).and_then(|j| bytes.get(j));
+            let separator = match (Some(i) == highlight, Some(i) == highlight.map(|h| h + 1)) {
+                (true, _) => '[',
+                (_, true) => ']',
+                _ => ' ',
+            };
+            out.push(separator);
+            match byte {
+                Some(byte) => {
+                    let _ = write!(out, "{:02x}", byte);
+                    ascii.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                        char::from(*byte)
+                    } else {
+                        '.'
+                    });
+                }
+                None => {
+                    out.push_str("  ");
+                    ascii.push(' ');
+                }
+            }
+        }
+        let closing = if highlight == Some(line + 15) {
+            ']'
+        } else {
+            ' '
+        };
+        let _ = writeln!(out, "{} |{}|", closing, ascii);
+    }
+    out
+}
+
+/// The outcome of every check [`SXGInput::verify`] performs.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationReport {
+    pub checks: Vec<Check>,
+}
+
+impl VerificationReport {
+    /// Whether every check passed, which is when [`SXGInput::verify`] returns `Ok(true)`.
+    pub fn verified(&self) -> bool {
+        self.checks.iter().all(|c| c.status == CheckStatus::Pass)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Fail)
+    }
+}
+
+fn pass(name: &'static str, detail: impl Into<String>) -> Check {
+    Check {
+        name,
+        status: CheckStatus::Pass,
+        detail: detail.into(),
+        mismatch: None,
+        hint: None,
+    }
+}
+
+fn fail(name: &'static str, detail: impl Into<String>) -> Check {
+    Check {
+        status: CheckStatus::Fail,
+        ..pass(name, detail)
+    }
+}
+
+fn skipped(name: &'static str, detail: impl Into<String>) -> Check {
+    Check {
+        status: CheckStatus::Skipped,
+        ..pass(name, detail)
+    }
+}
+
+/// Suggests the offsets at which `needle` actually occurs, for a wrong start index.
+fn offset_hint(index_field: &str, haystack: &[u8], needle: &[u8]) -> Option<String> {
+    match find_all(haystack, needle).as_slice() {
+        [] => None,
+        [offset] => Some(format!(
+            "the bytes occur at {}; set `{}` to it",
+            offset, index_field
+        )),
+        offsets => Some(format!(
+            "the bytes occur at {:?}; set `{}` to the intended one",
+            offsets, index_field
+        )),
+    }
+}
+
+fn compare(
+    name: &'static str,
+    field: &'static str,
+    index_field: &'static str,
+    haystack: &[u8],
+    range: Range<usize>,
+    expected: &[u8],
+) -> Check {
+    if haystack.get(range.clone()) == Some(expected) {
+        return pass(
+            name,
+            format!("{} matches at {}..{}", field, range.start, range.end),
+        );
+    }
+
+    let mismatch = Mismatch::new(field, haystack, range.start, expected);
+    let detail = if range.end > haystack.len() {
+        format!(
+            "{}..{} is out of bounds; `{}` is {} bytes",
+            range.start,
+            range.end,
+            field,
+            haystack.len()
+        )
+    } else {
+        format!(
+            "{} differs from the expected bytes at offset {}",
+            field, mismatch.first_difference
+        )
+    };
+    Check {
+        mismatch: Some(mismatch),
+        hint: offset_hint(index_field, haystack, expected),
+        ..fail(name, detail)
+    }
+}
+
+impl SXGInput {
+    /// Runs the checks of [`SXGInput::verify`] and reports on each of them.
+    pub fn verify_with_report(&self) -> VerificationReport {
+        let mut checks = Vec::new();
+
+        let start = self.data_to_verify_start_index;
+        checks.push(compare(
+            "disclosure",
+            "payload",
+            "data_to_verify_start_index",
+            &self.payload,
+            start..start.saturating_add(self.data_to_verify.len()),
+            &self.data_to_verify,
+        ));
+
+        let digest = mi_sha256_digest(&self.payload);
+        let start = self.integrity_start_index;
+        checks.push(compare(
+            "integrity",
+            "final_payload",
+            "integrity_start_index",
+            &self.final_payload,
+            start..start.saturating_add(digest.len()),
+            &digest,
+        ));
+
+        let signature = self.signature();
+        checks.push(match &signature {
+            Ok((r, s)) => pass(
+                "signature_encoding",
+                format!(
+                    "{} signature with {}-byte r and s",
+                    if self.sig.is_some() { "DER" } else { "raw" },
+                    r.len().max(s.len())
+                ),
+            ),
+            Err(e) => fail("signature_encoding", e.to_string()),
+        });
+
+        let public_key = self.public_key();
+        checks.push(match &public_key {
+            Ok(key) => pass(
+                "public_key",
+                format!("{:?} key 0x{}", self.algorithm, hex::encode(key)),
+            ),
+            Err(e) => fail("public_key", e.to_string()),
+        });
+
+        let signature = signature.ok().map(|(r, s)| {
+            let low_s = self.algorithm.normalize_s(&r, &s);
+            (r, s, low_s)
+        });
+        let (low_s_check, signature) = match signature {
+            None => (skipped("low_s", "the signature could not be decoded"), None),
+            Some((_, _, Err(e))) => (fail("low_s", e.to_string()), None),
+            Some((r, s, Ok(None))) => (pass("low_s", "s is low"), Some((r, s))),
+            Some((r, s, Ok(Some(low_s)))) => match self.low_s {
+                LowSPolicy::Accept => (
+                    pass("low_s", "s is high, which the `accept` policy allows"),
+                    Some((r, s)),
+                ),
+                LowSPolicy::Reject => (
+                    Check {
+                        hint: Some("set `low_s` to `normalize` to accept both forms".into()),
+                        ..fail("low_s", "s is high, which the `reject` policy refuses")
+                    },
+                    None,
+                ),
+                LowSPolicy::Normalize => (
+                    pass("low_s", "s is high and was normalized"),
+                    Some((r, low_s)),
+                ),
+            },
+        };
+        checks.push(low_s_check);
+
+        checks.push(match (signature, public_key) {
+            (Some((r, s)), Ok(key)) => {
+                let (px, py) = key[1..].split_at(self.algorithm.field_size());
+                match self
+                    .algorithm
+                    .verify_r_s(&self.final_payload, &r, &s, px, py)
+                {
+                    Ok(true) => pass("ecdsa", "the signature is valid for `final_payload`"),
+                    Ok(false) => Check {
+                        hint: Some(
+                            "check that `final_payload` is the complete signed message and that \
+                             the key belongs to the certificate the exchange names"
+                                .into(),
+                        ),
+                        ..fail(
+                            "ecdsa",
+                            "the signature does not verify over `final_payload`",
+                        )
+                    },
+                    Err(e) => fail("ecdsa", e.to_string()),
+                }
+            }
+            _ => skipped("ecdsa", "the signature or public key is unusable"),
+        });
+
+        VerificationReport { checks }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(report: &VerificationReport, name: &str) -> CheckStatus {
+        report
+            .checks
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn test_report_agrees_with_verify() {
+        for input in [
+            SXGInput::default_testcase_1(),
+            SXGInput::default_testcase_2(),
+        ] {
+            let report = input.verify_with_report();
+            assert!(report.verified(), "{:#?}", report);
+            assert_eq!(report.failures().count(), 0);
+        }
+
+        let mut input = SXGInput::default_testcase_2();
+        input.final_payload[0] = b'!';
+        let report = input.verify_with_report();
+        assert!(!report.verified());
+        assert!(!input.verify().unwrap());
+        assert_eq!(status(&report, "ecdsa"), CheckStatus::Fail);
+        assert_eq!(status(&report, "integrity"), CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_report_locates_wrong_offsets() {
+        let mut input = SXGInput::default_testcase_2();
+        input.data_to_verify_start_index += 1;
+        input.integrity_start_index -= 2;
+        let report = input.verify_with_report();
+        assert!(!input.verify().unwrap());
+
+        let disclosure = &report.checks[0];
+        assert_eq!(disclosure.status, CheckStatus::Fail);
+        let mismatch = disclosure.mismatch.as_ref().unwrap();
+        assert_eq!(mismatch.offset, 7505);
+        assert_eq!(mismatch.first_difference, 7505);
+        assert!(disclosure.hint.as_ref().unwrap().contains("7504"));
+
+        let integrity = &report.checks[1];
+        assert_eq!(integrity.status, CheckStatus::Fail);
+        assert!(integrity.hint.as_ref().unwrap().contains("349"));
+        assert!(integrity.mismatch.as_ref().unwrap().hexdump().contains('['));
+
+        // Still reported, not panicking, when the indices run off the end.
+        input.integrity_start_index = input.final_payload.len();
+        input.data_to_verify_start_index = usize::MAX;
+        let report = input.verify_with_report();
+        assert_eq!(report.failures().count(), 2);
+        assert!(!input.verify().unwrap());
+    }
+
+    #[test]
+    fn test_report_skips_dependent_checks() {
+        let mut input = SXGInput::default_testcase_1();
+        input.r.pop();
+        let report = input.verify_with_report();
+        assert_eq!(status(&report, "signature_encoding"), CheckStatus::Pass);
+        assert_eq!(status(&report, "low_s"), CheckStatus::Fail);
+        assert_eq!(status(&report, "ecdsa"), CheckStatus::Skipped);
+
+        let mut input = SXGInput::default_testcase_2();
+        input.low_s = LowSPolicy::Reject;
+        let report = input.verify_with_report();
+        assert_eq!(report.verified(), input.verify().unwrap());
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let dump = hexdump(b"0123456789abcdefXY", 14, Some(16));
+        let lines: Vec<_> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("00000000 "));
+        assert!(lines[0].ends_with("|              01|"), "{}", lines[0]);
+        assert!(lines[1].starts_with("00000010 [32]"), "{}", lines[1]);
+        assert!(lines[1].ends_with(" |23456789abcdefXY|"), "{}", lines[1]);
+    }
+}
@@ -0,0 +1,316 @@
+//! The versioned on-disk format for [`SXGInput`] files.
+//!
+//! Files are written as `{"version": 2, ...fields}`. Files without a version are either the
+//! original sxg-extension output (version 1), which is upgraded on load, or current-format files
+//! written before the version field existed.
+
+use std::fmt;
+
+use serde::{ser::Error as _, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{encoding::ByteEncoding, sxg::SXGInput, LowSPolicy, SignatureAlgorithm};
+
+pub const CURRENT_VERSION: u64 = 2;
+
+/// The JSON Schema for the current version, as checked in at `lib/schema/sxg-input.schema.json`.
+pub const SCHEMA_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/schema/sxg-input.schema.json");
+
+#[derive(Debug)]
+pub enum LoadError {
+    Json(serde_json::Error),
+    InvalidVersion(Value),
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Json(e) => write!(f, "invalid SXG input: {}", e),
+            LoadError::InvalidVersion(version) => {
+                write!(f, "`version` must be a positive integer, found {}", version)
+            }
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "input version {} is newer than the supported version {}",
+                version, CURRENT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> Self {
+        LoadError::Json(e)
+    }
+}
+
+/// The input as the sxg-extension first produced it: byte arrays and a fixed P-256 signature.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SXGInputV1 {
+    pub final_payload: Vec<u8>,
+    pub data_to_verify: Vec<u8>,
+    pub data_to_verify_start_index: usize,
+    pub integrity_start_index: usize,
+    pub payload: Vec<u8>,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub px: [u8; 32],
+    pub py: [u8; 32],
+}
+
+impl From<SXGInputV1> for SXGInput {
+    fn from(v1: SXGInputV1) -> Self {
+        SXGInput {
+            final_payload: v1.final_payload,
+            data_to_verify: v1.data_to_verify,
+            data_to_verify_start_index: v1.data_to_verify_start_index,
+            integrity_start_index: v1.integrity_start_index,
+            payload: v1.payload,
+            algorithm: SignatureAlgorithm::EcdsaP256Sha256,
+            r: v1.r.to_vec(),
+            s: v1.s.to_vec(),
+            sig: None,
+            px: v1.px.to_vec(),
+            py: v1.py.to_vec(),
+            public_key: None,
+            low_s: LowSPolicy::Accept,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Versioned<'a> {
+    version: u64,
+    #[serde(flatten)]
+    input: &'a SXGInput,
+}
+
+/// Loads an input file of any known version, upgrading it to the current [`SXGInput`].
+pub fn load_input(json: &str) -> Result<SXGInput, LoadError> {
+    let mut value: Value = serde_json::from_str(json)?;
+    let version = match value.as_object_mut().and_then(|o| o.remove("version")) {
+        None => 1,
+        Some(version) => match version.as_u64() {
+            Some(v) if v > 0 => v,
+            _ => return Err(LoadError::InvalidVersion(version)),
+        },
+    };
+
+    match version {
+        // Unversioned files that are not strictly version 1 predate the version field but
+        // already use the current fields, such as hex strings, `sig` or `low_s`. Both forms reject
+        // unknown fields, so a misspelt one is an error rather than silently dropped.
+        1 => match serde_json::from_value::<SXGInputV1>(value.clone()) {
+            Ok(v1) => Ok(v1.into()),
+            Err(_) => Ok(serde_json::from_value(value)?),
+        },
+        CURRENT_VERSION => Ok(serde_json::from_value(value)?),
+        _ => Err(LoadError::UnsupportedVersion(version)),
+    }
+}
+
+/// Writes `input` as a current-version file with byte fields in `byte_encoding`.
+pub fn save_input(input: &SXGInput, byte_encoding: ByteEncoding) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(Versioned {
+        version: CURRENT_VERSION,
+        input,
+    })?;
+    if byte_encoding != ByteEncoding::Hex {
+        for field in value
+            .as_object_mut()
+            .into_iter()
+            .flat_map(|o| o.values_mut())
+        {
+            let Some(bytes) = field.as_str().and_then(|s| s.strip_prefix("0x")) else {
+                continue;
+            };
+            let bytes = hex::decode(bytes).map_err(serde_json::Error::custom)?;
+            *field = byte_encoding.encode(&bytes).into();
+        }
+    }
+    serde_json::to_string_pretty(&value)
+}
+
+/// The JSON Schema (draft 2020-12) that current-version input files conform to.
+pub fn json_schema() -> Value {
+    let bytes = json!({ "$ref": "#/$defs/bytes" });
+    let optional_bytes = json!({ "anyOf": [{ "$ref": "#/$defs/bytes" }, { "type": "null" }] });
+    let index = json!({ "type": "integer", "minimum": 0 });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "https://github.com/crema-labs/sxg-sp1/lib/schema/sxg-input.schema.json",
+        "title": "SXGInput",
+        "description": "An SXG exchange and the part of its payload to disclose.",
+        "type": "object",
+        "additionalProperties": false,
+        "required": [
+            "version",
+            "final_payload",
+            "data_to_verify",
+            "data_to_verify_start_index",
+            "integrity_start_index",
+            "payload"
+        ],
+        "properties": {
+            "version": { "const": CURRENT_VERSION },
+            "final_payload": bytes,
+            "data_to_verify": bytes,
+            "data_to_verify_start_index": index,
+            "integrity_start_index": index,
+            "payload": bytes,
+            "algorithm": {
+                "enum": ["ecdsa_secp256r1_sha256", "ecdsa_secp384r1_sha384"],
+                "default": "ecdsa_secp256r1_sha256"
+            },
+            "r": bytes,
+            "s": bytes,
+            "sig": optional_bytes,
+            "px": bytes,
+            "py": bytes,
+            "public_key": optional_bytes,
+            "low_s": {
+                "enum": ["accept", "reject", "normalize"],
+                "default": "accept"
+            }
+        },
+        "allOf": [
+            { "anyOf": [{ "required": ["r", "s"] }, { "required": ["sig"] }] },
+            { "anyOf": [{ "required": ["px", "py"] }, { "required": ["public_key"] }] }
+        ],
+        "$defs": {
+            "bytes": {
+                "anyOf": [
+                    { "type": "string", "pattern": "^0x([0-9a-fA-F]{2})*$" },
+                    { "type": "string", "pattern": "^base64:[A-Za-z0-9+/]*={0,2}$" },
+                    {
+                        "description": "Bare base64, as written before the `base64:` prefix.",
+                        "deprecated": true,
+                        "type": "string",
+                        "pattern": "^(?!0x|base64:)[A-Za-z0-9+/]*={0,2}$"
+                    },
+                    {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_v1() {
+        let testcase = SXGInput::default_testcase_1();
+        let v1 = json!({
+            "final_payload": testcase.final_payload,
+            "data_to_verify": testcase.data_to_verify,
+            "data_to_verify_start_index": testcase.data_to_verify_start_index,
+            "integrity_start_index": testcase.integrity_start_index,
+            "payload": testcase.payload,
+            "r": testcase.r,
+            "s": testcase.s,
+            "px": testcase.px,
+            "py": testcase.py,
+        });
+
+        let input = load_input(&v1.to_string()).unwrap();
+        assert_eq!(input.algorithm, SignatureAlgorithm::EcdsaP256Sha256);
+        assert!(input.verify().unwrap());
+    }
+
+    #[test]
+    fn test_load_unversioned_current_fields() {
+        // Written with current fields before `version` existed: hex strings and a policy.
+        let input = SXGInput::default_testcase_2();
+        let mut value: Value =
+            serde_json::from_str(&save_input(&input, ByteEncoding::Hex).unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("version");
+        value["low_s"] = json!("reject");
+        let loaded = load_input(&value.to_string()).unwrap();
+        assert_eq!(loaded.low_s, LowSPolicy::Reject);
+        assert_eq!(loaded.payload, input.payload);
+
+        // Array fields with an extra current field are not silently read as version 1.
+        let testcase = SXGInput::default_testcase_1();
+        let v1_with_policy = json!({
+            "final_payload": testcase.final_payload,
+            "data_to_verify": testcase.data_to_verify,
+            "data_to_verify_start_index": testcase.data_to_verify_start_index,
+            "integrity_start_index": testcase.integrity_start_index,
+            "payload": testcase.payload,
+            "r": testcase.r,
+            "s": testcase.s,
+            "px": testcase.px,
+            "py": testcase.py,
+            "low_s": "normalize",
+        });
+        let loaded = load_input(&v1_with_policy.to_string()).unwrap();
+        assert_eq!(loaded.low_s, LowSPolicy::Normalize);
+        assert!(loaded.verify().unwrap());
+
+        assert!(load_input(r#"{"payload": "0x00"}"#).is_err());
+
+        // A misspelt field is rejected, not dropped in favour of the default policy.
+        let mut misspelt = v1_with_policy;
+        misspelt["low_S"] = misspelt.as_object_mut().unwrap().remove("low_s").unwrap();
+        let err = load_input(&misspelt.to_string()).unwrap_err();
+        assert!(err.to_string().contains("low_S"), "{}", err);
+    }
+
+    #[test]
+    fn test_save_and_load_current() {
+        let input = SXGInput::default_testcase_2();
+        for encoding in [ByteEncoding::Hex, ByteEncoding::Base64] {
+            let json = save_input(&input, encoding).unwrap();
+            let value: Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value["version"], CURRENT_VERSION);
+
+            let loaded = load_input(&json).unwrap();
+            assert_eq!(loaded.payload, input.payload);
+            assert!(loaded.verify().unwrap());
+        }
+
+        // Base64 of a field starting 0xD3 0x1? begins with "0x" and must not be read as hex.
+        let mut input = SXGInput::default_testcase_1();
+        input.data_to_verify = vec![0xd3, 0x10, 0x41, 0x42];
+        let json = save_input(&input, ByteEncoding::Base64).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["data_to_verify"], "base64:0xBBQg==");
+        assert_eq!(
+            load_input(&json).unwrap().data_to_verify,
+            input.data_to_verify
+        );
+
+        assert!(matches!(
+            load_input(r#"{"version": 99}"#),
+            Err(LoadError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            load_input(r#"{"version": "2"}"#),
+            Err(LoadError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_file_is_current() {
+        let expected = serde_json::to_string_pretty(&json_schema()).unwrap() + "\n";
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            std::fs::write(SCHEMA_PATH, &expected).unwrap();
+        }
+        let on_disk = std::fs::read_to_string(SCHEMA_PATH).unwrap_or_default();
+        assert!(
+            on_disk == expected,
+            "{} is out of date; regenerate it with `UPDATE_SCHEMA=1 cargo test -p lib schema`",
+            SCHEMA_PATH
+        );
+    }
+}
@@ -1,23 +1,48 @@
 use crate::{
-    sha256_hash,
+    der::decode_ecdsa_signature,
+    encoding::{self, ByteEncoding},
+    message::SignedMessage,
+    schema, sha256_hash,
     test_case_1::{DATA_TO_VERIFY_1, FINAL_PAYLOAD_1, PAYLOAD_1},
     test_case_2::{DATA_TO_VERIFY_2, FINAL_PAYLOAD_2, PAYLOAD_2},
-    verify_ecdsa_p256_r_s,
+    track_cycles, LowSPolicy, PublicValuesStruct, SignatureAlgorithm,
 };
 use base64::Engine;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SXGInput {
+    #[serde(with = "encoding")]
     pub final_payload: Vec<u8>,
+    #[serde(with = "encoding")]
     pub data_to_verify: Vec<u8>,
     pub data_to_verify_start_index: usize,
     pub integrity_start_index: usize,
+    #[serde(with = "encoding")]
     pub payload: Vec<u8>,
-    pub r: [u8; 32],
-    pub s: [u8; 32],
-    pub px: [u8; 32],
-    pub py: [u8; 32],
+    /// Inputs written before P-384 support carry no algorithm and are P-256.
+    #[serde(default)]
+    pub algorithm: SignatureAlgorithm,
+    /// Raw signature components, each `algorithm.field_size()` bytes. Left empty when the
+    /// signature is given as `sig` instead.
+    #[serde(default, with = "encoding")]
+    pub r: Vec<u8>,
+    #[serde(default, with = "encoding")]
+    pub s: Vec<u8>,
+    /// The DER-encoded `ECDSA-Sig-Value` taken verbatim from the SXG `sig` parameter.
+    #[serde(default, with = "encoding::option")]
+    pub sig: Option<Vec<u8>>,
+    /// Affine coordinates of the signing key. Left empty when the key is given as `public_key`.
+    #[serde(default, with = "encoding")]
+    pub px: Vec<u8>,
+    #[serde(default, with = "encoding")]
+    pub py: Vec<u8>,
+    /// The signing key as compressed or uncompressed SEC1, or as SubjectPublicKeyInfo DER.
+    #[serde(default, with = "encoding::option")]
+    pub public_key: Option<Vec<u8>>,
+    #[serde(default)]
+    pub low_s: LowSPolicy,
 }
 
 fn calculate_integrity(input: &[u8], record_size: usize) -> [u8; 32] {
@@ -51,33 +76,165 @@ fn calculate_integrity(input: &[u8], record_size: usize) -> [u8; 32] {
     proofs[0]
 }
 
+/// The record size the SXG signer uses for the mi-sha256 encoding of the payload.
+pub const MI_SHA256_RECORD_SIZE: usize = 16384;
+
+/// Returns the `mi-sha256-03=<base64 digest>` string the signed headers must carry for `payload`.
+pub fn mi_sha256_digest(payload: &[u8]) -> Vec<u8> {
+    mi_sha256_digest_with_record_size(payload, MI_SHA256_RECORD_SIZE)
+}
+
+/// Like [`mi_sha256_digest`], for payloads encoded with a record size other than the default.
+pub fn mi_sha256_digest_with_record_size(payload: &[u8], record_size: usize) -> Vec<u8> {
+    let prefix = (b"mi-sha256-03=").to_vec();
+    let payload = track_cycles!(
+        "mi_sha256",
+        calculate_integrity(payload, record_size).to_vec()
+    );
+
+    let mice_payload = track_cycles!("base64", base64::prelude::BASE64_STANDARD.encode(payload));
+    let mice = mice_payload.as_bytes();
+    [prefix, mice.to_vec()].concat()
+}
+
+/// The number of mi-sha256 records a payload of `payload_len` bytes is split into.
+pub fn mi_sha256_record_count(payload_len: usize, record_size: usize) -> usize {
+    payload_len.div_ceil(record_size)
+}
+
 impl SXGInput {
+    /// Serializes the input as a current-version input file with every byte field written in
+    /// `byte_encoding`.
+    pub fn to_json(&self, byte_encoding: ByteEncoding) -> serde_json::Result<String> {
+        schema::save_input(self, byte_encoding)
+    }
+
+    /// Returns the `(r, s)` pair to verify, decoding `sig` when it is present.
+    ///
+    /// Inputs carrying both forms must agree on them.
+    pub fn signature(&self) -> Result<(Vec<u8>, Vec<u8>), Box<dyn std::error::Error>> {
+        let Some(der) = &self.sig else {
+            return Ok((self.r.clone(), self.s.clone()));
+        };
+
+        let (r, s) = decode_ecdsa_signature(der, self.algorithm.field_size())?;
+        let raw_is_empty = self.r.is_empty() && self.s.is_empty();
+        if !raw_is_empty && (&r, &s) != (&self.r, &self.s) {
+            return Err("`sig` does not match the raw `r` and `s`".into());
+        }
+
+        Ok((r, s))
+    }
+
+    /// Returns the signing key as uncompressed SEC1, whichever form the input carries it in.
+    ///
+    /// Inputs carrying both `public_key` and `px`/`py` must agree on them.
+    pub fn public_key(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let from_coordinates = || {
+            let point = [&[4u8][..], &self.px, &self.py].concat();
+            self.algorithm.canonical_public_key(&point)
+        };
+
+        let Some(key) = &self.public_key else {
+            return from_coordinates();
+        };
+
+        let canonical = self.algorithm.canonical_public_key(key)?;
+        let coordinates_are_empty = self.px.is_empty() && self.py.is_empty();
+        if !coordinates_are_empty && from_coordinates()? != canonical {
+            return Err("`public_key` does not match `px` and `py`".into());
+        }
+
+        Ok(canonical)
+    }
+
+    /// Returns the signature with `s` replaced by its low form when it is high.
+    pub fn low_s_signature(&self) -> Result<(Vec<u8>, Vec<u8>), Box<dyn std::error::Error>> {
+        let (r, s) = self.signature()?;
+        let s = self.algorithm.normalize_s(&r, &s)?.unwrap_or(s);
+        Ok((r, s))
+    }
+
+    /// The `sha256(r || s)` of the low-S signature, which is identical for both malleable
+    /// forms. Only produced under [`LowSPolicy::Normalize`].
+    pub fn canonical_signature_hash(&self) -> Result<Option<[u8; 32]>, Box<dyn std::error::Error>> {
+        if self.low_s != LowSPolicy::Normalize {
+            return Ok(None);
+        }
+        let (r, s) = self.low_s_signature()?;
+        Ok(Some(sha256_hash(&[r, s].concat())))
+    }
+
     pub fn verify(&self) -> Result<bool, Box<dyn std::error::Error>> {
-        if self.payload[self.data_to_verify_start_index
-            ..self.data_to_verify_start_index + self.data_to_verify.len()]
-            != self.data_to_verify
-        {
+        let disclosed = track_cycles!("disclosure", {
+            self.payload.get(
+                self.data_to_verify_start_index
+                    ..self
+                        .data_to_verify_start_index
+                        .saturating_add(self.data_to_verify.len()),
+            ) == Some(&self.data_to_verify[..])
+        });
+        if !disclosed {
             return Ok(false);
         }
 
-        let prefix = (b"mi-sha256-03=").to_vec();
-        let payload = calculate_integrity(&self.payload, 16384).to_vec();
-
-        let mice_payload = base64::prelude::BASE64_STANDARD.encode(payload);
-        let mice = mice_payload.as_bytes();
-        let mice_bytes = [prefix, mice.to_vec()].concat();
+        let mice_bytes = mi_sha256_digest(&self.payload);
 
-        if self.final_payload
-            [self.integrity_start_index..self.integrity_start_index + mice_bytes.len()]
-            != mice_bytes[..]
-        {
+        let signed_digest = self.final_payload.get(
+            self.integrity_start_index..self.integrity_start_index.saturating_add(mice_bytes.len()),
+        );
+        if signed_digest != Some(&mice_bytes[..]) {
             return Ok(false);
         }
 
-        Ok(
-            verify_ecdsa_p256_r_s(&self.final_payload, &self.r, &self.s, &self.px, &self.py)
-                .is_ok(),
-        )
+        track_cycles!("signature", self.verify_signature())
+    }
+
+    /// Checks the signature over `final_payload`, applying the `low_s` policy.
+    fn verify_signature(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let (r, s) = match self.low_s {
+            LowSPolicy::Accept => self.signature()?,
+            LowSPolicy::Reject => {
+                let (r, s) = self.signature()?;
+                if self.algorithm.normalize_s(&r, &s)?.is_some() {
+                    return Ok(false);
+                }
+                (r, s)
+            }
+            LowSPolicy::Normalize => self.low_s_signature()?,
+        };
+
+        let public_key = self.public_key()?;
+        let (px, py) = public_key[1..].split_at(self.algorithm.field_size());
+
+        Ok(self
+            .algorithm
+            .verify_r_s(&self.final_payload, &r, &s, px, py)
+            .unwrap_or(false))
+    }
+
+    /// The values the guest commits for this input, given the outcome of [`SXGInput::verify`].
+    ///
+    /// The signature hash, public key and URL are left empty when they cannot be derived.
+    pub fn public_values(&self, verified: bool) -> PublicValuesStruct {
+        let signature_hash = self
+            .canonical_signature_hash()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let public_key = self.public_key().unwrap_or_default();
+        let url = SignedMessage::parse(&self.final_payload)
+            .map(|message| String::from_utf8_lossy(message.request_url).into_owned())
+            .unwrap_or_default();
+
+        PublicValuesStruct {
+            result: verified as u32,
+            data_to_verify: self.data_to_verify.clone(),
+            signature_hash: signature_hash.into(),
+            public_key: public_key.into(),
+            url,
+            low_s_policy: self.low_s as u8,
+        }
     }
 
     pub fn default_testcase_1() -> SXGInput {
@@ -106,10 +263,14 @@ impl SXGInput {
             data_to_verify_start_index,
             integrity_start_index,
             payload: payload.to_vec(),
-            r: r.try_into().unwrap(),
-            s: s.try_into().unwrap(),
-            px: px.try_into().unwrap(),
-            py: py.try_into().unwrap(),
+            algorithm: SignatureAlgorithm::EcdsaP256Sha256,
+            r,
+            s,
+            px,
+            py,
+            sig: None,
+            public_key: None,
+            low_s: LowSPolicy::Accept,
         }
     }
 
@@ -139,17 +300,25 @@ impl SXGInput {
             data_to_verify_start_index,
             integrity_start_index,
             payload: payload.to_vec(),
-            r: r.try_into().unwrap(),
-            s: s.try_into().unwrap(),
-            px: px.try_into().unwrap(),
-            py: py.try_into().unwrap(),
+            algorithm: SignatureAlgorithm::EcdsaP256Sha256,
+            r,
+            s,
+            px,
+            py,
+            sig: None,
+            public_key: None,
+            low_s: LowSPolicy::Accept,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sxg::SXGInput;
+    use crate::{
+        combined_hash, encoding::ByteEncoding, sha256_hash, sxg::SXGInput, LowSPolicy,
+        PublicValuesStruct, SignatureAlgorithm,
+    };
+    use alloy_sol_types::SolType;
 
     #[test]
     fn test_sxg() {
@@ -159,4 +328,184 @@ mod tests {
         let default_input = SXGInput::default_testcase_2();
         assert!(default_input.verify().unwrap());
     }
+
+    #[test]
+    fn test_public_values() {
+        let input_1 = SXGInput::default_testcase_1();
+        let input_2 = SXGInput::default_testcase_2();
+        let values_1 = input_1.public_values(true);
+        let values_2 = input_2.public_values(false);
+        assert_eq!(values_1.result, 1);
+        assert_eq!(values_2.result, 0);
+        assert_eq!(values_1.url, "https://blog.crema.sh/");
+        assert_eq!(values_2.data_to_verify, input_2.data_to_verify);
+
+        // The combined hash chains the digests of the single-exchange public values, in order.
+        let digests = [&values_1, &values_2]
+            .map(|values| sha256_hash(&PublicValuesStruct::abi_encode(values)))
+            .concat();
+        let exchanges = [values_1, values_2];
+        assert_eq!(combined_hash(&exchanges), sha256_hash(&digests));
+        assert_ne!(
+            combined_hash(&exchanges),
+            combined_hash(&[exchanges[1].clone(), exchanges[0].clone()])
+        );
+    }
+
+    #[test]
+    fn test_sxg_der_signature() {
+        let raw = SXGInput::default_testcase_1();
+
+        let mut der = vec![0x30, 0x45, 0x02, 0x21, 0x00];
+        der.extend(&raw.r);
+        der.extend([0x02, 0x20]);
+        der.extend(&raw.s);
+
+        // The DER form alone is enough.
+        let mut input = SXGInput::default_testcase_1();
+        input.r.clear();
+        input.s.clear();
+        input.sig = Some(der.clone());
+        assert!(input.verify().unwrap());
+
+        // Both forms are fine as long as they agree.
+        let mut input = SXGInput::default_testcase_1();
+        input.sig = Some(der);
+        assert!(input.verify().unwrap());
+
+        input.s[31] ^= 1;
+        assert!(input.verify().is_err());
+
+        let json = serde_json::to_string(&raw).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("sig");
+        let input: SXGInput = serde_json::from_value(value).unwrap();
+        assert!(input.verify().unwrap());
+    }
+
+    #[test]
+    fn test_sxg_p384() {
+        use p384::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let mut input = SXGInput::default_testcase_1();
+        let signing_key = SigningKey::from_slice(&[7u8; 48]).unwrap();
+        let signature: Signature = signing_key.sign(&input.final_payload);
+        let point = signing_key.verifying_key().to_encoded_point(false);
+
+        input.algorithm = SignatureAlgorithm::EcdsaP384Sha384;
+        input.r = signature.r().to_bytes().to_vec();
+        input.s = signature.s().to_bytes().to_vec();
+        input.px = point.x().unwrap().to_vec();
+        input.py = point.y().unwrap().to_vec();
+        assert!(input.verify().unwrap());
+
+        input.sig = Some(signature.to_der().as_bytes().to_vec());
+        input.r.clear();
+        input.s.clear();
+        assert!(input.verify().unwrap());
+
+        // A P-256 key cannot pass for a P-384 one.
+        let p256_input = SXGInput::default_testcase_1();
+        input.px = p256_input.px;
+        input.py = p256_input.py;
+        assert!(input.verify().is_err());
+    }
+
+    #[test]
+    fn test_sxg_low_s_policy() {
+        // The signature in test case 2 has a high `s`.
+        let high = SXGInput::default_testcase_2();
+        let mut low = SXGInput::default_testcase_2();
+        low.s = high
+            .algorithm
+            .normalize_s(&high.r, &high.s)
+            .unwrap()
+            .unwrap();
+        assert!(low.verify().unwrap());
+        assert!(low.algorithm.normalize_s(&low.r, &low.s).unwrap().is_none());
+
+        let mut input = SXGInput::default_testcase_2();
+        input.low_s = LowSPolicy::Reject;
+        assert!(!input.verify().unwrap());
+        low.low_s = LowSPolicy::Reject;
+        assert!(low.verify().unwrap());
+
+        assert_eq!(high.canonical_signature_hash().unwrap(), None);
+
+        input.low_s = LowSPolicy::Normalize;
+        low.low_s = LowSPolicy::Normalize;
+        assert!(input.verify().unwrap());
+        assert!(low.verify().unwrap());
+        assert_eq!(
+            input.canonical_signature_hash().unwrap(),
+            low.canonical_signature_hash().unwrap()
+        );
+        assert!(input.canonical_signature_hash().unwrap().is_some());
+
+        // A verifier can tell the policies apart from the public values alone.
+        let accepted = low.public_values(true);
+        low.low_s = LowSPolicy::Reject;
+        let rejected = low.public_values(true);
+        assert_eq!(accepted.low_s_policy, LowSPolicy::Normalize as u8);
+        assert_eq!(
+            LowSPolicy::from_committed(rejected.low_s_policy),
+            Some(LowSPolicy::Reject)
+        );
+        assert_ne!(
+            PublicValuesStruct::abi_encode(&accepted),
+            PublicValuesStruct::abi_encode(&rejected)
+        );
+        assert_eq!(LowSPolicy::from_committed(3), None);
+    }
+
+    #[test]
+    fn test_sxg_public_key_forms() {
+        let raw = SXGInput::default_testcase_1();
+        let uncompressed = raw.public_key().unwrap();
+        assert_eq!(uncompressed, [&[4u8][..], &raw.px, &raw.py].concat());
+
+        let mut compressed = vec![2 + (raw.py[31] & 1)];
+        compressed.extend(&raw.px);
+        let mut spki = hex::decode("3059301306072a8648ce3d020106082a8648ce3d030107034200").unwrap();
+        spki.extend(&uncompressed);
+
+        for key in [compressed, uncompressed.clone(), spki] {
+            let mut input = SXGInput::default_testcase_1();
+            input.px.clear();
+            input.py.clear();
+            input.public_key = Some(key);
+            assert_eq!(input.public_key().unwrap(), uncompressed);
+            assert!(input.verify().unwrap());
+        }
+
+        let mut input = SXGInput::default_testcase_1();
+        input.public_key = Some(SXGInput::default_testcase_2().public_key().unwrap());
+        assert!(input.verify().is_err());
+    }
+
+    #[test]
+    fn test_sxg_json_encodings() {
+        let input = SXGInput::default_testcase_2();
+
+        let array_json = r#"{"final_payload": [1, 2], "data_to_verify": [], "data_to_verify_start_index": 0,
+            "integrity_start_index": 0, "payload": [3], "r": [4], "s": [5], "px": [6], "py": [7]}"#;
+        let from_array: SXGInput = serde_json::from_str(array_json).unwrap();
+        assert_eq!(from_array.final_payload, [1, 2]);
+        assert_eq!(from_array.py, [7]);
+
+        for encoding in [ByteEncoding::Hex, ByteEncoding::Base64] {
+            let json = input.to_json(encoding).unwrap();
+            let decoded = crate::schema::load_input(&json).unwrap();
+            assert_eq!(decoded.final_payload, input.final_payload);
+            assert_eq!(decoded.payload, input.payload);
+            assert_eq!(decoded.r, input.r);
+            assert!(decoded.verify().unwrap());
+        }
+
+        let hex_json = input.to_json(ByteEncoding::Hex).unwrap();
+        let base64_json = input.to_json(ByteEncoding::Base64).unwrap();
+        assert!(hex_json.contains(r#""r": "0x8f05b0dc"#));
+        assert!(base64_json.contains(r#""r": "base64:jwWw3"#));
+        assert!(base64_json.len() < hex_json.len());
+    }
 }
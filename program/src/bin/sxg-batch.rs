@@ -0,0 +1,33 @@
+//! Verifies several SXG inputs in one execution, so one proof covers all of them.
+//!
+//! An input that fails verification is committed with `result = 0` instead of aborting the run.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+
+use lib::sxg::SXGInput;
+use lib::{combined_hash, track_cycles, BatchPublicValuesStruct};
+pub fn main() {
+    let sxg_inputs = track_cycles!("deserialize", sp1_zkvm::io::read::<Vec<SXGInput>>());
+
+    let exchanges: Vec<_> = sxg_inputs
+        .iter()
+        .map(|sxg_input| {
+            let verified = sxg_input.verify().unwrap_or(false);
+            track_cycles!("public_values", sxg_input.public_values(verified))
+        })
+        .collect();
+    let combined_hash = combined_hash(&exchanges);
+
+    let bytes = track_cycles!(
+        "abi_encode",
+        BatchPublicValuesStruct::abi_encode(&BatchPublicValuesStruct {
+            exchanges,
+            combined_hash: combined_hash.into(),
+        })
+    );
+
+    sp1_zkvm::io::commit_slice(&bytes);
+}
@@ -4,15 +4,13 @@ sp1_zkvm::entrypoint!(main);
 use alloy_sol_types::SolType;
 
 use lib::sxg::SXGInput;
-use lib::PublicValuesStruct;
+use lib::{track_cycles, PublicValuesStruct};
 pub fn main() {
-    let sxg_input = sp1_zkvm::io::read::<SXGInput>();
-    let result = sxg_input.verify().unwrap() as u32;
+    let sxg_input = track_cycles!("deserialize", sp1_zkvm::io::read::<SXGInput>());
+    let verified = sxg_input.verify().unwrap();
 
-    let bytes = PublicValuesStruct::abi_encode(&PublicValuesStruct {
-        result,
-        data_to_verify: sxg_input.data_to_verify,
-    });
+    let public_values = track_cycles!("public_values", sxg_input.public_values(verified));
+    let bytes = track_cycles!("abi_encode", PublicValuesStruct::abi_encode(&public_values));
 
     sp1_zkvm::io::commit_slice(&bytes);
 }
@@ -1,5 +1,26 @@
-use sp1_helper::build_program_with_args;
+use sp1_helper::{build_program_with_args, BuildArgs};
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    // The program crate has one binary per entry point; each is written to its own ELF.
+    for (binary, elf_name) in [
+        ("sxg-program", "riscv32im-succinct-zkvm-elf"),
+        ("sxg-batch", "sxg-batch-elf"),
+    ] {
+        build_program_with_args(
+            "../program",
+            BuildArgs {
+                binary: binary.to_string(),
+                elf_name: elf_name.to_string(),
+                ..Default::default()
+            },
+        )
+    }
+
+    build_program_with_args(
+        "../aggregation-program",
+        BuildArgs {
+            elf_name: "sxg-aggregation-elf".to_string(),
+            ..Default::default()
+        },
+    )
 }
@@ -0,0 +1,254 @@
+//! An async front for [`SxgProver`], for Tokio services. Enabled by the `async` feature.
+//!
+//! Proving blocks a thread for minutes, so every job runs on Tokio's blocking pool, at most
+//! `max_jobs` at a time, and never on a runtime worker. Setup runs on the first job. Each job
+//! reports its [`Progress`] over a channel and can be cancelled: a job still waiting for a slot
+//! stops at once, and a running one at the start of its next stage, because SP1 cannot interrupt
+//! a proof in progress.
+//!
+//! ```no_run
+//! # async fn run(input: lib::sxg::SXGInput) -> sxg_script::Result<()> {
+//! use sxg_script::{async_prover::AsyncSxgProver, prover::ProofKind};
+//!
+//! let prover = AsyncSxgProver::new(1);
+//! let mut job = prover.prove(input, ProofKind::Groth16);
+//! while let Some(progress) = job.next_progress().await {
+//!     println!("{:?}", progress);
+//! }
+//! let proof = job.wait().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{error::Error, fmt, future::Future, sync::Arc};
+
+use lib::sxg::SXGInput;
+use tokio::{
+    sync::{mpsc, watch, OnceCell, Semaphore},
+    task::{JoinError, JoinHandle},
+};
+
+use crate::{
+    keys::KeyCache,
+    proof_cache::ProofCache,
+    prover::{Execution, Progress, ProofKind, SxgProof, SxgProver},
+    Result,
+};
+
+/// Errors that can cross threads, which `crate::Result`'s cannot.
+type SendError = Box<dyn Error + Send + Sync>;
+
+/// The error a cancelled job fails with. Check for it with `error.is::<Cancelled>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the proving job was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+fn sendable(e: Box<dyn Error>) -> SendError {
+    if e.is::<Cancelled>() {
+        Box::new(Cancelled)
+    } else {
+        e.to_string().into()
+    }
+}
+
+/// Cancels the job it came from. Cloning it is cheap.
+#[derive(Clone)]
+pub struct CancelHandle(Arc<watch::Sender<bool>>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+type Setup = dyn Fn() -> Result<SxgProver> + Send + Sync;
+
+/// Runs [`SxgProver`] jobs on Tokio's blocking pool. Cloning it shares the prover and its job
+/// limit.
+#[derive(Clone)]
+pub struct AsyncSxgProver {
+    setup: Arc<Setup>,
+    prover: Arc<OnceCell<SxgProver>>,
+    slots: Arc<Semaphore>,
+}
+
+impl AsyncSxgProver {
+    /// A prover like [`SxgProver::new`], running at most `max_jobs` jobs at once.
+    pub fn new(max_jobs: usize) -> Self {
+        Self::with_setup(max_jobs, || Ok(SxgProver::new()))
+    }
+
+    /// Like [`AsyncSxgProver::new`], with keys from `keys` and proofs reused from `cache`.
+    pub fn with_caches(max_jobs: usize, keys: KeyCache, cache: Option<ProofCache>) -> Self {
+        let cache = std::sync::Mutex::new(cache);
+        Self::with_setup(max_jobs, move || {
+            let prover = SxgProver::with_key_cache(&keys)?;
+            Ok(match cache.lock().unwrap().take() {
+                Some(cache) => prover.with_proof_cache(cache),
+                None => prover,
+            })
+        })
+    }
+
+    /// A prover like [`SxgProver::mock`].
+    pub fn mock(max_jobs: usize) -> Self {
+        Self::with_setup(max_jobs, || Ok(SxgProver::mock()))
+    }
+
+    /// Wraps `prover`, whose setup has already run.
+    pub fn from_prover(max_jobs: usize, prover: SxgProver) -> Self {
+        AsyncSxgProver {
+            prover: Arc::new(OnceCell::new_with(Some(prover))),
+            ..Self::with_setup(max_jobs, || Err("the prover was already set up".into()))
+        }
+    }
+
+    /// A prover that `setup` creates on the blocking pool when the first job needs it. If setup
+    /// fails, that job fails with its error and the next job tries again.
+    pub fn with_setup(
+        max_jobs: usize,
+        setup: impl Fn() -> Result<SxgProver> + Send + Sync + 'static,
+    ) -> Self {
+        AsyncSxgProver {
+            setup: Arc::new(setup),
+            prover: Arc::new(OnceCell::new()),
+            slots: Arc::new(Semaphore::new(max_jobs.max(1))),
+        }
+    }
+
+    /// Runs the program on `input` without proving, see [`SxgProver::execute`].
+    pub async fn execute(&self, input: SXGInput) -> Result<Execution> {
+        let job = self.run(None, None, move |prover| {
+            prover.execute(&input).map_err(sendable)
+        });
+        join(job).await
+    }
+
+    /// Starts proving `input` as a `kind` proof, see [`SxgProver::prove`]. Must be called from
+    /// within a Tokio runtime. Dropping the job cancels it.
+    pub fn prove(&self, input: SXGInput, kind: ProofKind) -> ProofJob {
+        let (progress_tx, progress) = mpsc::unbounded_channel();
+        let (cancel_tx, cancel) = watch::channel(false);
+
+        let task = self.run(
+            Some(cancel.clone()),
+            Some(progress_tx.clone()),
+            move |prover| {
+                prover
+                    .prove_with_progress(&input, kind, |stage| {
+                        if *cancel.borrow() {
+                            return Err(Box::new(Cancelled));
+                        }
+                        // The receiver only goes away with the job, which cancels it.
+                        let _ = progress_tx.send(stage);
+                        Ok(())
+                    })
+                    .map_err(sendable)
+            },
+        );
+
+        ProofJob {
+            cancel: CancelHandle(Arc::new(cancel_tx)),
+            progress,
+            task,
+        }
+    }
+
+    /// Waits for a slot, unless `cancel` fires first, sets the prover up if no job has yet,
+    /// reporting it to `progress`, and runs `job` on the blocking pool. Without `cancel` the job
+    /// cannot be cancelled.
+    fn run<T: Send + 'static>(
+        &self,
+        mut cancel: Option<watch::Receiver<bool>>,
+        progress: Option<mpsc::UnboundedSender<Progress>>,
+        job: impl FnOnce(&SxgProver) -> std::result::Result<T, SendError> + Send + 'static,
+    ) -> JoinHandle<std::result::Result<T, SendError>> {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let slot = this.slots.clone().acquire_owned();
+            let _slot = match &mut cancel {
+                // Also fires if the sender is gone, which only happens when the job is dropped.
+                Some(cancel) => tokio::select! {
+                    slot = slot => slot?,
+                    _ = cancel.wait_for(|&cancelled| cancelled) => {
+                        return Err(Box::new(Cancelled) as SendError)
+                    }
+                },
+                None => slot.await?,
+            };
+            if cancel.is_some_and(|cancel| *cancel.borrow()) {
+                return Err(Box::new(Cancelled));
+            }
+
+            // Concurrent first jobs wait for one setup instead of each running their own.
+            this.prover
+                .get_or_try_init(|| async {
+                    if let Some(progress) = &progress {
+                        let _ = progress.send(Progress::Setup);
+                    }
+                    let setup = this.setup.clone();
+                    tokio::task::spawn_blocking(move || setup().map_err(sendable)).await?
+                })
+                .await?;
+
+            let prover = this.prover.clone();
+            tokio::task::spawn_blocking(move || {
+                job(prover.get().expect("the prover was set up above"))
+            })
+            .await?
+        })
+    }
+}
+
+async fn join<T>(
+    task: impl Future<Output = std::result::Result<std::result::Result<T, SendError>, JoinError>>,
+) -> Result<T> {
+    match task.await {
+        Ok(result) => result.map_err(|e| e as Box<dyn Error>),
+        Err(e) => Err(format!("the proving job panicked: {}", e).into()),
+    }
+}
+
+/// A proof being generated by [`AsyncSxgProver::prove`].
+pub struct ProofJob {
+    cancel: CancelHandle,
+    progress: mpsc::UnboundedReceiver<Progress>,
+    task: JoinHandle<std::result::Result<SxgProof, SendError>>,
+}
+
+impl ProofJob {
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// The next stage the job started, or `None` once it has finished.
+    pub async fn next_progress(&mut self) -> Option<Progress> {
+        self.progress.recv().await
+    }
+
+    /// Waits for the job to finish. A cancelled job fails with [`Cancelled`].
+    pub async fn wait(mut self) -> Result<SxgProof> {
+        join(&mut self.task).await
+    }
+}
+
+impl Drop for ProofJob {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
@@ -0,0 +1,131 @@
+//! Checked-in cycle counts for the bundled inputs, to catch the guest getting slower.
+//!
+//! `script/tests/cycles.rs` executes the guest on every bundled input and fails when one takes
+//! more cycles than its baseline plus the tolerance, or has no baseline. `sxg baseline update`, or
+//! running the test with [`UPDATE_ENV`] set, rewrites the baseline after an intentional change.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lib::sxg::SXGInput;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+use crate::{Result, SXG_ELF};
+
+/// Set to `1` to have `script/tests/cycles.rs` record the measured counts instead of checking them.
+pub const UPDATE_ENV: &str = "SXG_UPDATE_BASELINE";
+
+/// Whether [`UPDATE_ENV`] asks for the baseline to be rewritten.
+pub fn update_requested() -> bool {
+    std::env::var(UPDATE_ENV).is_ok_and(|value| value == "1")
+}
+
+/// The inputs the baseline covers, relative to the `script` directory.
+pub const BUNDLED_INPUTS: [&str; 2] = ["sxg_input_1.json", "sxg_input_2.json"];
+
+/// The checked-in baseline, in the `script` directory.
+pub fn default_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("cycle-baseline.json")
+}
+
+/// Loads a bundled input by its name in [`BUNDLED_INPUTS`].
+pub fn load_bundled(name: &str) -> Result<SXGInput> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(name);
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    Ok(lib::schema::load_input(&json)?)
+}
+
+/// Executes the SXG program on `input` and returns its cycle count.
+pub fn execute_cycles(client: &ProverClient, input: &SXGInput) -> Result<u64> {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(input);
+    let (_, report) = client.execute(SXG_ELF, stdin).run()?;
+    Ok(report.total_instruction_count())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleBaseline {
+    /// How much slower than its baseline an input may get, in percent.
+    pub tolerance_percent: f64,
+    /// Cycle counts by input name.
+    pub cycles: BTreeMap<String, u64>,
+}
+
+impl Default for CycleBaseline {
+    fn default() -> Self {
+        CycleBaseline {
+            tolerance_percent: 2.0,
+            cycles: BTreeMap::new(),
+        }
+    }
+}
+
+/// How an input's cycle count compares to its baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Comparison {
+    /// The baseline has no count for the input yet.
+    Missing,
+    Within {
+        baseline: u64,
+        change_percent: f64,
+    },
+    /// Faster by more than the tolerance, so the baseline is out of date.
+    Faster {
+        baseline: u64,
+        change_percent: f64,
+    },
+    Slower {
+        baseline: u64,
+        change_percent: f64,
+    },
+}
+
+impl Comparison {
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Comparison::Slower { .. })
+    }
+}
+
+impl CycleBaseline {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("invalid cycle baseline in {}: {}", path.display(), e).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    pub fn compare(&self, name: &str, cycles: u64) -> Comparison {
+        let Some(&baseline) = self.cycles.get(name) else {
+            return Comparison::Missing;
+        };
+        let change_percent = 100.0 * (cycles as f64 - baseline as f64) / baseline.max(1) as f64;
+        if change_percent > self.tolerance_percent {
+            Comparison::Slower {
+                baseline,
+                change_percent,
+            }
+        } else if change_percent < -self.tolerance_percent {
+            Comparison::Faster {
+                baseline,
+                change_percent,
+            }
+        } else {
+            Comparison::Within {
+                baseline,
+                change_percent,
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! The REST API: submit an input, poll its job, and download the proof or fixture.
+//!
+//! | Request                   | Response                                                  |
+//! |---------------------------|-----------------------------------------------------------|
+//! | `POST /jobs?proof_type=…` | `202` and the queued job; the body is an SXG input file   |
+//! | `GET /jobs/<id>`          | the job, with its status and, once done, result or error  |
+//! | `GET /jobs/<id>/proof`    | the saved `SP1ProofWithPublicValues`                      |
+//! | `GET /jobs/<id>/fixture`  | the Solidity fixture, for groth16 and plonk jobs          |
+//! | `GET /health`             | queue length and capacity                                 |
+//!
+//! Errors are `{"error": "..."}`. A full queue answers `503`, and a proof or fixture that is not
+//! ready yet `409`.
+
+use std::{fs, io::Read};
+
+use clap::ValueEnum;
+use lib::schema::load_input;
+use serde::Serialize;
+use serde_json::json;
+use sxg_script::ProofType;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    jobs::{JobStatus, FIXTURE_FILE, PROOF_FILE},
+    Queue,
+};
+
+/// Input files larger than this are rejected with `413`.
+const MAX_INPUT_BYTES: usize = 16 * 1024 * 1024;
+
+type HttpResponse = Response<std::io::Cursor<Vec<u8>>>;
+
+fn json_response(status: u16, body: &impl Serialize) -> HttpResponse {
+    let body = serde_json::to_vec_pretty(body).unwrap_or_default();
+    Response::from_data(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn error(status: u16, message: impl std::fmt::Display) -> HttpResponse {
+    json_response(status, &json!({ "error": message.to_string() }))
+}
+
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn submit(request: &mut Request, queue: &Queue) -> HttpResponse {
+    let proof_type = match query_param(request.url(), "proof_type") {
+        None => ProofType::Core,
+        Some(value) => match ProofType::from_str(value, true) {
+            Ok(proof_type) => proof_type,
+            Err(_) => return error(400, format!("unknown proof_type {:?}", value)),
+        },
+    };
+
+    if request.body_length().unwrap_or(0) > MAX_INPUT_BYTES {
+        return error(413, "input is too large");
+    }
+    let mut json = String::new();
+    let mut reader = request.as_reader().take(MAX_INPUT_BYTES as u64 + 1);
+    if let Err(e) = reader.read_to_string(&mut json) {
+        return error(400, format!("failed to read the request body: {}", e));
+    }
+    if json.len() > MAX_INPUT_BYTES {
+        return error(413, "input is too large");
+    }
+
+    if let Err(e) = load_input(&json) {
+        return error(400, format!("invalid input: {}", e));
+    }
+
+    match queue.submit(&json, proof_type) {
+        Ok(Some(job)) => json_response(202, &job),
+        Ok(None) => error(503, "the job queue is full; try again later")
+            .with_header(Header::from_bytes("Retry-After", "30").unwrap()),
+        Err(e) => error(500, e),
+    }
+}
+
+fn download(queue: &Queue, id: &str, file: &str) -> HttpResponse {
+    let job = match queue.store.get(id) {
+        Ok(Some(job)) => job,
+        Ok(None) => return error(404, "no such job"),
+        Err(e) => return error(500, e),
+    };
+    match job.status {
+        JobStatus::Succeeded => {}
+        JobStatus::Failed => return error(409, "the job failed; see its error"),
+        JobStatus::Queued | JobStatus::Running => return error(409, "the job is not done yet"),
+    }
+    if file == FIXTURE_FILE && !job.proof_type.is_evm() {
+        return error(404, "only groth16 and plonk jobs have a fixture");
+    }
+
+    match fs::read(queue.store.path(id, file)) {
+        Ok(bytes) => Response::from_data(bytes)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap()),
+        Err(e) => error(500, format!("failed to read {}: {}", file, e)),
+    }
+}
+
+pub fn handle(mut request: Request, queue: &Queue) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let response = match (request.method(), segments.as_slice()) {
+        (Method::Get, ["health"]) => json_response(200, &queue.health()),
+        (Method::Post, ["jobs"]) => submit(&mut request, queue),
+        (Method::Get, ["jobs", id]) => match queue.store.get(id) {
+            Ok(Some(job)) => json_response(200, &job),
+            Ok(None) => error(404, "no such job"),
+            Err(e) => error(500, e),
+        },
+        (Method::Get, ["jobs", id, "proof"]) => download(queue, id, PROOF_FILE),
+        (Method::Get, ["jobs", id, "fixture"]) => download(queue, id, FIXTURE_FILE),
+        (_, ["health"] | ["jobs", ..]) => error(405, "method not allowed"),
+        _ => error(404, "not found"),
+    };
+
+    let status = response.status_code().0;
+    tracing::debug!("{} {} -> {}", request.method(), request.url(), status);
+    if let Err(e) = request.respond(response) {
+        tracing::warn!("failed to send a response: {}", e);
+    }
+}
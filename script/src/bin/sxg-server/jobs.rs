@@ -0,0 +1,167 @@
+//! Proving jobs persisted on the local filesystem, one directory per job.
+//!
+//! A job's directory holds the submitted input, `job.json` with its status, and the proof and
+//! fixture once it succeeds. `job.json` is replaced through a rename on every change, so after a
+//! crash it always holds a state the job was really in.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lib::{schema::load_input, sxg::SXGInput};
+use serde::{Deserialize, Serialize};
+use sxg_script::{write_json, ProofType, Result};
+
+pub const JOB_FILE: &str = "job.json";
+pub const INPUT_FILE: &str = "input.json";
+pub const PROOF_FILE: &str = "proof-with-io.json";
+pub const FIXTURE_FILE: &str = "fixture.json";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    /// Whether the job still has to be proven, including jobs a previous run was interrupted in.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub proof_type: ProofType,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// How many times a worker has started the job. More than one means it was resumed after
+    /// the server stopped while proving it.
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The SXG verification result the proof commits to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vkey: Option<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub struct JobStore {
+    dir: PathBuf,
+    counter: AtomicU64,
+}
+
+impl JobStore {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        Ok(JobStore {
+            dir: dir.to_path_buf(),
+            counter: AtomicU64::new(0),
+        })
+    }
+
+    /// The path of `file` in the job's directory. `id` must have come from [`JobStore::get`] or
+    /// [`JobStore::create`].
+    pub fn path(&self, id: &str, file: &str) -> PathBuf {
+        self.dir.join(id).join(file)
+    }
+
+    /// Stores the input file `json` as a new queued job.
+    pub fn create(&self, json: &str, proof_type: ProofType) -> Result<Job> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let id = format!(
+            "{:x}-{:x}",
+            nanos,
+            self.counter.fetch_add(1, Ordering::Relaxed)
+        );
+        let dir = self.dir.join(&id);
+        fs::create_dir(&dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        fs::write(dir.join(INPUT_FILE), json)?;
+
+        let job = Job {
+            id,
+            status: JobStatus::Queued,
+            proof_type,
+            created_at: now(),
+            updated_at: now(),
+            attempts: 0,
+            error: None,
+            result: None,
+            vkey: None,
+        };
+        self.save(&job)?;
+        Ok(job)
+    }
+
+    /// Returns the job, or `None` if there is none with this id.
+    pub fn get(&self, id: &str) -> Result<Option<Job>> {
+        // Ids are only ever hex and dashes, which also keeps them from escaping the store.
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Ok(None);
+        }
+        let path = self.path(id, JOB_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    pub fn save(&self, job: &Job) -> Result<()> {
+        let path = self.path(&job.id, JOB_FILE);
+        let partial = path.with_extension("partial");
+        write_json(
+            &partial,
+            &Job {
+                updated_at: now(),
+                ..job.clone()
+            },
+        )?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    pub fn input(&self, id: &str) -> Result<SXGInput> {
+        let path = self.path(id, INPUT_FILE);
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Ok(load_input(&json)?)
+    }
+
+    /// Jobs that are queued or were interrupted while running, oldest first.
+    pub fn pending(&self) -> Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let id = entry?.file_name().to_string_lossy().into_owned();
+            match self.get(&id) {
+                Ok(Some(job)) if job.status.is_pending() => jobs.push(job),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("skipping unreadable job {}: {}", id, e),
+            }
+        }
+        jobs.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        Ok(jobs)
+    }
+}
@@ -0,0 +1,321 @@
+//! `sxg-server`: a local proving service with a REST API, for apps that would otherwise shell out
+//! to `sxg`.
+//!
+//! Submitted inputs are stored as jobs under `--jobs-dir` and proven in the background by
+//! `--workers` threads. At most `--queue-capacity` jobs wait at a time; further submissions are
+//! turned away until the queue drains. Jobs that were queued or running when the server stopped
+//! are picked up again on the next start. See [`api`] for the endpoints.
+//!
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin sxg-server -- --listen 127.0.0.1:3000
+//! ```
+
+mod api;
+mod jobs;
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Mutex,
+    },
+    thread,
+};
+
+use alloy_sol_types::SolType;
+use clap::Parser;
+use lib::PublicValuesStruct;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProvingKey, SP1VerifyingKey};
+use sxg_script::{
+    keys::KeyCache, proof_cache::ProofCache, write_json, ProofType, Result, SP1SXGProofFixture,
+};
+
+use jobs::{Job, JobStatus, JobStore, FIXTURE_FILE, PROOF_FILE};
+
+/// Prove SXG inputs locally, behind a REST API.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    /// Address to listen on. Keep it on loopback unless something in front handles access.
+    #[clap(long, env = "SXG_SERVER_LISTEN", default_value = "127.0.0.1:3000")]
+    listen: String,
+
+    /// Directory that jobs, their inputs and their proofs are stored in.
+    #[clap(long, env = "SXG_JOBS_DIR", default_value = ".sxg-jobs")]
+    jobs_dir: PathBuf,
+
+    /// Most jobs that may wait to be proven at once.
+    #[clap(long, default_value_t = 16, value_parser = clap::value_parser!(u16).range(1..))]
+    queue_capacity: u16,
+
+    /// Jobs proven at the same time. Every worker needs a prover's worth of memory.
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    workers: u16,
+
+    /// Use SP1's mock prover, like `sxg --mock`.
+    #[clap(long)]
+    mock: bool,
+
+    #[clap(flatten)]
+    keys: KeyCache,
+
+    #[clap(flatten)]
+    proof_cache: ProofCache,
+}
+
+#[derive(Serialize)]
+struct Health {
+    queued: usize,
+    capacity: usize,
+}
+
+/// The bounded queue of job ids waiting for a worker, in front of the job store.
+pub struct Queue {
+    store: JobStore,
+    sender: SyncSender<String>,
+    receiver: Mutex<Receiver<String>>,
+    queued: AtomicUsize,
+    capacity: usize,
+}
+
+impl Queue {
+    fn new(store: JobStore, capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        Queue {
+            store,
+            sender,
+            receiver: Mutex::new(receiver),
+            queued: AtomicUsize::new(0),
+            capacity,
+        }
+    }
+
+    /// Stores and enqueues a new job for the input file `json`, which must already have been
+    /// checked to load. Returns `None` without storing anything when the queue is full. A job
+    /// that is stored but cannot be enqueued is marked failed, so it is not resumed later.
+    pub fn submit(&self, json: &str, proof_type: ProofType) -> Result<Option<Job>> {
+        // Claim a slot first, so a job is never stored that the queue then has no room for.
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.capacity {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Ok(None);
+        }
+        let job = match self.store.create(json, proof_type) {
+            Ok(job) => job,
+            Err(e) => {
+                self.queued.fetch_sub(1, Ordering::SeqCst);
+                return Err(e);
+            }
+        };
+        // The claimed slot guarantees room in the channel, so this only fails once the workers
+        // are gone.
+        if let Err(e) = self.sender.try_send(job.id.clone()) {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            let error = format!("failed to queue job {}: {}", job.id, e);
+            let failed = Job {
+                status: JobStatus::Failed,
+                error: Some(error.clone()),
+                ..job
+            };
+            if let Err(e) = self.store.save(&failed) {
+                tracing::warn!("failed to mark job {} failed: {}", failed.id, e);
+            }
+            return Err(error.into());
+        }
+        Ok(Some(job))
+    }
+
+    /// Re-enqueues jobs left pending by a previous run, waiting for room as needed. If the
+    /// workers are gone, the rest stay pending for the next start.
+    fn resume(&self, pending: Vec<Job>) {
+        for mut job in pending {
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Queued;
+                // Enqueue it regardless: the worker records its status again on pickup.
+                if let Err(e) = self.store.save(&job) {
+                    tracing::warn!("failed to mark job {} queued: {}", job.id, e);
+                }
+            }
+            tracing::info!("resuming job {}", job.id);
+            self.queued.fetch_add(1, Ordering::SeqCst);
+            if self.sender.send(job.id).is_err() {
+                self.queued.fetch_sub(1, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+
+    fn next(&self) -> Option<String> {
+        let id = self.receiver.lock().unwrap().recv().ok()?;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        Some(id)
+    }
+
+    fn health(&self) -> Health {
+        Health {
+            queued: self.queued.load(Ordering::SeqCst),
+            capacity: self.capacity,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Proves one job, recording the outcome in its `job.json`.
+fn run_job(
+    client: &ProverClient,
+    (pk, vk): (&SP1ProvingKey, &SP1VerifyingKey),
+    cache: &ProofCache,
+    store: &JobStore,
+    id: &str,
+) -> Result<()> {
+    let Some(mut job) = store.get(id)? else {
+        return Err(format!("job {} disappeared", id).into());
+    };
+    job.status = JobStatus::Running;
+    job.attempts += 1;
+    store.save(&job)?;
+    tracing::info!("proving job {} (attempt {})", id, job.attempts);
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| -> Result<u32> {
+        let input = store.input(id)?;
+        let (proof, _) = cache.prove(client, (pk, vk), &input, job.proof_type)?;
+        proof.save(store.path(id, PROOF_FILE))?;
+
+        let result = PublicValuesStruct::abi_decode(proof.public_values.as_slice(), true)?.result;
+        if job.proof_type.is_evm() {
+            let fixture = SP1SXGProofFixture::new(result, vk, &proof);
+            write_json(&store.path(id, FIXTURE_FILE), &fixture)?;
+        }
+        Ok(result)
+    }));
+
+    match outcome {
+        Ok(Ok(result)) => {
+            job.status = JobStatus::Succeeded;
+            job.result = Some(result);
+            job.vkey = Some(vk.bytes32());
+        }
+        Ok(Err(e)) => {
+            job.status = JobStatus::Failed;
+            job.error = Some(e.to_string());
+        }
+        Err(payload) => {
+            job.status = JobStatus::Failed;
+            job.error = Some(format!("panicked: {}", panic_message(payload)));
+        }
+    }
+    tracing::info!("job {} {:?}", id, job.status);
+    store.save(&job)
+}
+
+fn serve(cli: Cli) -> Result<()> {
+    let store = JobStore::open(&cli.jobs_dir)?;
+    let pending = store.pending()?;
+    let queue = Queue::new(store, usize::from(cli.queue_capacity));
+
+    let client = ProverClient::new();
+    let (pk, vk) = cli.keys.keys(&client)?;
+
+    let server = tiny_http::Server::http(&cli.listen)
+        .map_err(|e| format!("failed to listen on {}: {}", cli.listen, e))?;
+    tracing::info!("listening on http://{}", cli.listen);
+
+    thread::scope(|scope| {
+        for _ in 0..cli.workers {
+            scope.spawn(|| {
+                while let Some(id) = queue.next() {
+                    if let Err(e) =
+                        run_job(&client, (&pk, &vk), &cli.proof_cache, &queue.store, &id)
+                    {
+                        tracing::error!("job {}: {}", id, e);
+                    }
+                }
+            });
+        }
+        scope.spawn(|| queue.resume(pending));
+
+        for request in server.incoming_requests() {
+            api::handle(request, &queue);
+        }
+    });
+    Ok(())
+}
+
+fn main() {
+    sp1_sdk::utils::setup_logger();
+
+    let cli = Cli::parse();
+    if cli.mock {
+        std::env::set_var("SP1_PROVER", "mock");
+        tracing::warn!("using the mock prover; proofs will not verify on a real verifier");
+    }
+
+    if let Err(e) = serve(cli) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(name: &str, capacity: usize) -> (Queue, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("sxg-server-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        (Queue::new(JobStore::open(&dir).unwrap(), capacity), dir)
+    }
+
+    #[test]
+    fn unqueued_job_is_not_left_pending() {
+        let (queue, dir) = queue("unqueued", 2);
+        // Drop the receiving end, as if the workers had stopped.
+        *queue.receiver.lock().unwrap() = mpsc::sync_channel(1).1;
+
+        assert!(queue.submit("{}", ProofType::Core).is_err());
+        assert_eq!(queue.health().queued, 0);
+        assert!(queue.store.pending().unwrap().is_empty());
+
+        let id = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .file_name();
+        let job = queue.store.get(&id.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn interrupted_job_is_resumed() {
+        let (queue, dir) = queue("resumed", 2);
+        let mut job = queue.store.create("{}", ProofType::Core).unwrap();
+        job.status = JobStatus::Running;
+        queue.store.save(&job).unwrap();
+
+        queue.resume(queue.store.pending().unwrap());
+        assert_eq!(queue.health().queued, 1);
+        assert_eq!(queue.next(), Some(job.id.clone()));
+        assert_eq!(
+            queue.store.get(&job.id).unwrap().unwrap().status,
+            JobStatus::Queued
+        );
+
+        assert!(queue.submit("{}", ProofType::Core).unwrap().is_some());
+        assert!(queue.submit("{}", ProofType::Core).unwrap().is_some());
+        assert!(queue.submit("{}", ProofType::Core).unwrap().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
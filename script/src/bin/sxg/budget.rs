@@ -0,0 +1,61 @@
+//! A cycle budget that proving commands check their inputs against before starting.
+
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use sxg_script::estimate::{CycleModel, InputFeatures};
+
+use crate::common::Result;
+
+/// What to do when an input is predicted to exceed `--cycle-budget`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum OverBudget {
+    Warn,
+    Refuse,
+}
+
+#[derive(Args, Debug)]
+pub struct BudgetArgs {
+    /// Cycle model written by `sxg calibrate`. The built-in model is used otherwise.
+    #[clap(long, env = "SXG_CYCLE_MODEL")]
+    pub cycle_model: Option<PathBuf>,
+
+    /// Most cycles an input may be predicted to take before `--over-budget` applies.
+    #[clap(long, env = "SXG_CYCLE_BUDGET")]
+    pub cycle_budget: Option<u64>,
+
+    /// Whether to warn and prove anyway, or refuse to prove, when over budget.
+    #[clap(long, value_enum, default_value = "warn")]
+    pub over_budget: OverBudget,
+}
+
+impl BudgetArgs {
+    pub fn model(&self) -> Result<CycleModel> {
+        match &self.cycle_model {
+            Some(path) => CycleModel::load(path),
+            None => Ok(CycleModel::default()),
+        }
+    }
+
+    /// Estimates the cycles for `features` and applies the budget, naming the input `what` in
+    /// the warning or error. Returns the estimate.
+    pub fn check(&self, model: &CycleModel, features: &InputFeatures, what: &str) -> Result<u64> {
+        let estimate = model.estimate(features);
+        tracing::info!("{} is estimated at {} cycles", what, estimate);
+
+        let Some(budget) = self.cycle_budget else {
+            return Ok(estimate);
+        };
+        if estimate > budget {
+            let message = format!(
+                "{} is estimated at {} cycles, over the budget of {}",
+                what, estimate, budget
+            );
+            match self.over_budget {
+                OverBudget::Warn => tracing::warn!("{}", message),
+                OverBudget::Refuse => return Err(message.into()),
+            }
+        }
+        Ok(estimate)
+    }
+}
@@ -0,0 +1,150 @@
+//! `sxg aggregate`: combines compressed SXG proofs into one proof of the aggregation program.
+//!
+//! Every proof is checked on the host against the SXG program's verifying key, then verified
+//! again inside the aggregation program, which commits the SXG vkey hash and each proof's
+//! public-values digest. A single groth16 or plonk proof then stands for all of them.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1Stdin};
+
+use crate::{
+    common::{
+        write_json, DecodedAggregationPublicValues, DecodedPublicValues, OutputArgs, ProofType,
+        Result, SP1AggregationProofFixture, SXG_AGGREGATION_ELF,
+    },
+    keys::KeyCache,
+};
+
+#[derive(Args, Debug)]
+pub struct AggregateArgs {
+    /// Paths to compressed proofs saved by `sxg prove --proof-type compressed`, in the order their
+    /// digests are committed.
+    #[clap(value_parser, required = true)]
+    pub proofs: Vec<PathBuf>,
+
+    /// The kind of aggregate proof to generate. Groth16 and plonk proofs also get an
+    /// `SP1AggregationProofFixture`.
+    #[clap(long, value_enum, default_value = "groth16")]
+    pub proof_type: ProofType,
+
+    /// Name the proof and fixture files are written under.
+    #[clap(long, default_value = "aggregate")]
+    pub name: String,
+}
+
+#[derive(Serialize)]
+struct Aggregated {
+    proof_type: ProofType,
+    vkey: String,
+    public_values: DecodedAggregationPublicValues,
+    exchanges: Vec<DecodedPublicValues>,
+    proof_path: String,
+    fixture_path: Option<String>,
+}
+
+pub fn run(args: &AggregateArgs, keys: &KeyCache, output: &OutputArgs) -> Result<()> {
+    // A core proof cannot be verified recursively, so it cannot be aggregated any further either.
+    if args.proof_type == ProofType::Core {
+        return Err("aggregate proofs must be compressed, groth16 or plonk".into());
+    }
+
+    let client = ProverClient::new();
+    let (_, sxg_vk) = keys.keys(&client)?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&sxg_vk.hash_u32());
+
+    let mut public_values = Vec::with_capacity(args.proofs.len());
+    let mut reduce_proofs = Vec::with_capacity(args.proofs.len());
+    let mut exchanges = Vec::with_capacity(args.proofs.len());
+    for path in &args.proofs {
+        let proof = SP1ProofWithPublicValues::load(path)
+            .map_err(|e| format!("failed to load {}: {}", path.display(), e))?;
+        client.verify(&proof, &sxg_vk).map_err(|e| {
+            format!(
+                "{} does not verify against the SXG program {}: {}",
+                path.display(),
+                sxg_vk.bytes32(),
+                e
+            )
+        })?;
+        let SP1Proof::Compressed(reduce_proof) = proof.proof else {
+            return Err(format!(
+                "{} is not a compressed proof; prove it with --proof-type compressed",
+                path.display()
+            )
+            .into());
+        };
+
+        exchanges.push(DecodedPublicValues::decode(proof.public_values.as_slice())?);
+        public_values.push(proof.public_values.to_vec());
+        reduce_proofs.push(*reduce_proof);
+    }
+
+    stdin.write(&public_values);
+    for reduce_proof in reduce_proofs {
+        stdin.write_proof(reduce_proof, sxg_vk.vk.clone());
+    }
+
+    let (pk, vk) = keys.keys_for(&client, SXG_AGGREGATION_ELF)?;
+    let builder = client.prove(&pk, stdin);
+    let proof = match args.proof_type {
+        ProofType::Core => unreachable!("rejected above"),
+        ProofType::Compressed => builder.compressed().run(),
+        ProofType::Groth16 => builder.groth16().run(),
+        ProofType::Plonk => builder.plonk().run(),
+    }?;
+
+    client.verify(&proof, &vk)?;
+
+    let proof_path = output.path(&format!("{}-proof-with-io.json", args.name))?;
+    proof.save(&proof_path)?;
+
+    let aggregated_values = DecodedAggregationPublicValues::decode(proof.public_values.as_slice())?;
+    let all_verified = exchanges.iter().all(|exchange| exchange.result == 1);
+    let vkey = vk.bytes32();
+
+    let fixture_path = if args.proof_type.is_evm() {
+        let fixture = SP1AggregationProofFixture::new(&vk, &sxg_vk, &proof);
+        let path = output.path(&format!("{}-fixture.json", args.name))?;
+        write_json(&path, &fixture)?;
+        Some(path.display().to_string())
+    } else {
+        None
+    };
+
+    let aggregated = Aggregated {
+        proof_type: args.proof_type,
+        vkey,
+        public_values: aggregated_values,
+        exchanges,
+        proof_path: proof_path.display().to_string(),
+        fixture_path,
+    };
+
+    output.emit(&aggregated, |a| {
+        println!(
+            "Successfully generated and verified {:?} proof aggregating {} proofs!",
+            a.proof_type,
+            a.exchanges.len()
+        );
+        for (i, exchange) in a.exchanges.iter().enumerate() {
+            println!("Exchange {}:", i);
+            exchange.print();
+        }
+        a.public_values.print();
+        println!("Verification Key: {}", a.vkey);
+        println!("Proof: {}", a.proof_path);
+        if let Some(fixture_path) = &a.fixture_path {
+            println!("Fixture: {}", fixture_path);
+        }
+    })?;
+
+    if !all_verified {
+        return Err("the proof is valid, but not every aggregated SXG check passed".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,138 @@
+//! `sxg baseline`: checks or rewrites the cycle counts `script/tests/cycles.rs` compares against.
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use sp1_sdk::ProverClient;
+use sxg_script::baseline::{self, Comparison, CycleBaseline, BUNDLED_INPUTS};
+
+use crate::common::{OutputArgs, Result};
+
+#[derive(Args, Debug)]
+pub struct BaselineArgs {
+    /// The baseline file. Defaults to the one checked in next to the bundled inputs.
+    #[clap(long)]
+    pub baseline: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: BaselineCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BaselineCommand {
+    /// Execute the bundled inputs and compare their cycle counts to the baseline. Fails on a
+    /// regression or an input missing from the baseline.
+    Check,
+    /// Execute the bundled inputs and record their cycle counts as the new baseline.
+    Update {
+        /// How much slower than the baseline an input may get, in percent. Keeps the current
+        /// tolerance when omitted.
+        #[clap(long)]
+        tolerance: Option<f64>,
+    },
+}
+
+#[derive(Serialize)]
+struct InputCycles {
+    input: String,
+    cycles: u64,
+    #[serde(flatten)]
+    comparison: Comparison,
+}
+
+#[derive(Serialize)]
+struct BaselineReport {
+    baseline_path: String,
+    tolerance_percent: f64,
+    updated: bool,
+    inputs: Vec<InputCycles>,
+}
+
+pub fn run(args: &BaselineArgs, output: &OutputArgs) -> Result<()> {
+    let path = args.baseline.clone().unwrap_or_else(baseline::default_path);
+    let mut current = if path.exists() {
+        CycleBaseline::load(&path)?
+    } else {
+        CycleBaseline::default()
+    };
+
+    let client = ProverClient::new();
+    let mut inputs = Vec::with_capacity(BUNDLED_INPUTS.len());
+    for name in BUNDLED_INPUTS {
+        tracing::info!("executing {}", name);
+        let cycles = baseline::execute_cycles(&client, &baseline::load_bundled(name)?)?;
+        inputs.push(InputCycles {
+            input: name.to_string(),
+            cycles,
+            comparison: current.compare(name, cycles),
+        });
+    }
+
+    let updated = matches!(args.command, BaselineCommand::Update { .. });
+    if let BaselineCommand::Update { tolerance } = args.command {
+        if let Some(tolerance) = tolerance {
+            current.tolerance_percent = tolerance;
+        }
+        current.cycles = inputs
+            .iter()
+            .map(|input| (input.input.clone(), input.cycles))
+            .collect();
+        current.save(&path)?;
+    }
+
+    let report = BaselineReport {
+        baseline_path: path.display().to_string(),
+        tolerance_percent: current.tolerance_percent,
+        updated,
+        inputs,
+    };
+
+    output.emit(&report, |r| {
+        for input in &r.inputs {
+            let comparison = match input.comparison {
+                Comparison::Missing => "new".to_string(),
+                Comparison::Within { change_percent, .. } => format!("{:+.1}%", change_percent),
+                Comparison::Faster { change_percent, .. } => {
+                    format!("{:+.1}% (faster)", change_percent)
+                }
+                Comparison::Slower { change_percent, .. } => {
+                    format!("{:+.1}% (SLOWER)", change_percent)
+                }
+            };
+            println!(
+                "{:<24} {:>12} cycles  {}",
+                input.input, input.cycles, comparison
+            );
+        }
+        if r.updated {
+            println!("Updated {}", r.baseline_path);
+        }
+    })?;
+
+    let regressions = report
+        .inputs
+        .iter()
+        .filter(|input| input.comparison.is_regression())
+        .count();
+    let missing = report
+        .inputs
+        .iter()
+        .filter(|input| input.comparison == Comparison::Missing)
+        .count();
+    if !updated && regressions > 0 {
+        return Err(format!(
+            "{} inputs are more than {}% slower than the baseline",
+            regressions, report.tolerance_percent
+        )
+        .into());
+    }
+    if !updated && missing > 0 {
+        return Err(format!(
+            "{} inputs have no baseline; run `sxg baseline update` to record them",
+            missing
+        )
+        .into());
+    }
+    Ok(())
+}
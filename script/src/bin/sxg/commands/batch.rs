@@ -0,0 +1,367 @@
+//! `sxg batch`: proves every input in a directory or JSONL manifest and writes a summary.
+//!
+//! Each input is executed first, for its cycle count and to skip proving inputs that fail inside
+//! the zkVM, and then proven exactly as `sxg prove` would. A failing or panicking input is
+//! recorded in the summary and the batch carries on.
+
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use clap::Args;
+use lib::{schema::load_input, sxg::SXGInput};
+use serde::Serialize;
+use serde_json::Value;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProvingKey, SP1Stdin, SP1VerifyingKey};
+use sxg_script::{
+    estimate::{CycleModel, InputFeatures},
+    proof_cache::ProofCache,
+};
+
+use crate::{
+    budget::BudgetArgs,
+    commands::prove::prove_input,
+    common::{write_json, DecodedPublicValues, OutputArgs, ProofType, Result, SXG_ELF},
+    keys::KeyCache,
+};
+
+/// The summary file written into `--output-dir`.
+pub const SUMMARY_FILE: &str = "batch-summary.json";
+
+/// Files `sxg` itself writes, which are skipped when a directory is scanned for inputs.
+const OUTPUT_SUFFIXES: [&str; 2] = ["-proof-with-io.json", "-fixture.json"];
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// A directory of `*.json` input files, or a `.jsonl` manifest whose lines are either
+    /// `{"path": "<input file>"}` or an inline input. Either form may carry a `"name"` that the
+    /// output files are named after.
+    #[clap(value_parser)]
+    pub inputs: PathBuf,
+
+    /// The kind of proof to generate. Only groth16 and plonk proofs can be verified on-chain.
+    #[clap(long, value_enum, default_value = "core")]
+    pub proof_type: ProofType,
+
+    /// Inputs proven at the same time. Every worker needs a prover's worth of memory.
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: u16,
+
+    #[clap(flatten)]
+    pub budget: BudgetArgs,
+}
+
+/// One input of the batch. Inputs that fail to load are still listed, so they show up in the
+/// summary.
+struct BatchItem {
+    name: String,
+    source: String,
+    input: std::result::Result<SXGInput, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Success,
+    Failure,
+}
+
+#[derive(Serialize)]
+struct ItemSummary {
+    name: String,
+    source: String,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_cycles: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cycles: Option<u64>,
+    wall_time_secs: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixture_path: Option<String>,
+    cached: bool,
+}
+
+#[derive(Serialize)]
+struct BatchSummary {
+    proof_type: ProofType,
+    vkey: String,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    wall_time_secs: f64,
+    results: Vec<ItemSummary>,
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "sxg".to_string())
+}
+
+fn load_file(path: &Path) -> std::result::Result<SXGInput, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    load_input(&json).map_err(|e| e.to_string())
+}
+
+fn scan_directory(dir: &Path) -> Result<Vec<BatchItem>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|path| {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        file_name.ends_with(".json")
+            && file_name != SUMMARY_FILE
+            && !OUTPUT_SUFFIXES.iter().any(|s| file_name.ends_with(s))
+    });
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .map(|path| BatchItem {
+            name: stem(&path),
+            source: path.display().to_string(),
+            input: load_file(&path),
+        })
+        .collect())
+}
+
+fn read_manifest(manifest: &Path) -> Result<Vec<BatchItem>> {
+    let contents = fs::read_to_string(manifest)
+        .map_err(|e| format!("failed to read {}: {}", manifest.display(), e))?;
+    let base = manifest.parent().unwrap_or(Path::new("."));
+
+    let mut items = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 1;
+        let source = format!("{}:{}", manifest.display(), line_number);
+        let default_name = format!("{}-{}", stem(manifest), line_number);
+
+        let mut entry: Value = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                items.push(BatchItem {
+                    name: default_name,
+                    source,
+                    input: Err(format!("invalid manifest line: {}", e)),
+                });
+                continue;
+            }
+        };
+        let fields = entry.as_object_mut();
+        let name = fields
+            .as_ref()
+            .and_then(|f| f.get("name")?.as_str().map(str::to_lowercase));
+        let path = fields
+            .as_ref()
+            .and_then(|f| Some(base.join(f.get("path")?.as_str()?)));
+        if let Some(fields) = fields {
+            fields.remove("name");
+        }
+
+        items.push(match path {
+            Some(path) => BatchItem {
+                name: name.unwrap_or_else(|| stem(&path)),
+                source: path.display().to_string(),
+                input: load_file(&path),
+            },
+            None => BatchItem {
+                name: name.unwrap_or(default_name),
+                source,
+                input: load_input(&entry.to_string()).map_err(|e| e.to_string()),
+            },
+        });
+    }
+    Ok(items)
+}
+
+/// Suffixes repeated names with their position, so no two inputs write the same output files.
+fn deduplicate_names(mut items: Vec<BatchItem>) -> Vec<BatchItem> {
+    let mut seen = std::collections::HashSet::new();
+    for (i, item) in items.iter_mut().enumerate() {
+        if !seen.insert(item.name.clone()) {
+            item.name = format!("{}-{}", item.name, i + 1);
+            seen.insert(item.name.clone());
+        }
+    }
+    items
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn prove_item(
+    client: &ProverClient,
+    keys: (&SP1ProvingKey, &SP1VerifyingKey),
+    cache: &ProofCache,
+    item: &BatchItem,
+    args: &BatchArgs,
+    model: &CycleModel,
+    output: &OutputArgs,
+) -> ItemSummary {
+    let started = Instant::now();
+    let mut summary = ItemSummary {
+        name: item.name.clone(),
+        source: item.source.clone(),
+        status: Status::Failure,
+        error: None,
+        result: None,
+        estimated_cycles: None,
+        cycles: None,
+        wall_time_secs: 0.0,
+        proof_path: None,
+        fixture_path: None,
+        cached: false,
+    };
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| -> Result<()> {
+        let sxg_input = item.input.as_ref().map_err(|e| e.clone())?;
+        let features = InputFeatures::of(sxg_input);
+        summary.estimated_cycles = Some(model.estimate(&features));
+        args.budget.check(model, &features, &item.name)?;
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(sxg_input);
+        let (public_values, report) = client.execute(SXG_ELF, stdin).run()?;
+        summary.cycles = Some(report.total_instruction_count());
+        let public_values = DecodedPublicValues::decode(public_values.as_slice())?;
+        summary.result = Some(public_values.result);
+        if public_values.result != 1 {
+            return Err("SXG verification failed inside the zkVM".into());
+        }
+
+        let proved = prove_input(
+            client,
+            keys,
+            cache,
+            sxg_input,
+            &item.name,
+            args.proof_type,
+            output,
+        )?;
+        summary.proof_path = Some(proved.proof_path);
+        summary.fixture_path = proved.fixture_path;
+        summary.cached = proved.cached;
+        Ok(())
+    }));
+
+    match outcome {
+        Ok(Ok(())) => summary.status = Status::Success,
+        Ok(Err(e)) => summary.error = Some(e.to_string()),
+        Err(payload) => summary.error = Some(format!("panicked: {}", panic_message(payload))),
+    }
+    summary.wall_time_secs = started.elapsed().as_secs_f64();
+    summary
+}
+
+fn print(summary: &BatchSummary) {
+    for item in &summary.results {
+        let status = match item.status {
+            Status::Success if item.cached => "ok (cached)".to_string(),
+            Status::Success => "ok".to_string(),
+            Status::Failure => format!("FAILED: {}", item.error.as_deref().unwrap_or_default()),
+        };
+        let cycles = item.cycles.map(|c| c.to_string()).unwrap_or_default();
+        println!(
+            "{:<32} {:>12} cycles {:>8.1}s  {}",
+            item.name, cycles, item.wall_time_secs, status
+        );
+    }
+    println!(
+        "{} of {} inputs proven in {:.1}s ({} failed)",
+        summary.succeeded, summary.total, summary.wall_time_secs, summary.failed
+    );
+    println!("Verification Key: {}", summary.vkey);
+}
+
+pub fn run(
+    args: &BatchArgs,
+    keys: &KeyCache,
+    cache: &ProofCache,
+    output: &OutputArgs,
+) -> Result<()> {
+    let items = if args.inputs.is_dir() {
+        scan_directory(&args.inputs)?
+    } else {
+        read_manifest(&args.inputs)?
+    };
+    if items.is_empty() {
+        return Err(format!("no inputs found in {}", args.inputs.display()).into());
+    }
+    let items = deduplicate_names(items);
+
+    let model = args.budget.model()?;
+    let started = Instant::now();
+    let client = ProverClient::new();
+    let (pk, vk) = keys.keys(&client)?;
+
+    // Workers take the next unclaimed input until none are left.
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    let workers = usize::from(args.jobs).min(items.len());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else { break };
+                tracing::info!("proving {} ({}/{})", item.name, i + 1, items.len());
+                let summary = prove_item(&client, (&pk, &vk), cache, item, args, &model, output);
+                results.lock().unwrap().push((i, summary));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(i, _)| *i);
+    let results: Vec<ItemSummary> = results.into_iter().map(|(_, summary)| summary).collect();
+    let succeeded = results
+        .iter()
+        .filter(|r| matches!(r.status, Status::Success))
+        .count();
+
+    let summary = BatchSummary {
+        proof_type: args.proof_type,
+        vkey: vk.bytes32(),
+        total: results.len(),
+        succeeded,
+        failed: results.len() - succeeded,
+        wall_time_secs: started.elapsed().as_secs_f64(),
+        results,
+    };
+    write_json(&output.path(SUMMARY_FILE)?, &summary)?;
+    output.emit(&summary, print)?;
+
+    if summary.failed > 0 {
+        return Err(format!(
+            "{} of {} inputs failed; see {}",
+            summary.failed,
+            summary.total,
+            output.path(SUMMARY_FILE)?.display()
+        )
+        .into());
+    }
+    Ok(())
+}
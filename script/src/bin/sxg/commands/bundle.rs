@@ -0,0 +1,154 @@
+//! `sxg bundle`: converts existing outputs to `.sxgproof` bundles and prints bundles.
+
+use std::{fs, path::PathBuf};
+
+use clap::Subcommand;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues};
+use sxg_script::bundle::{ProofBundle, BUNDLE_EXTENSION};
+
+use crate::{
+    common::{OutputArgs, ProofType, Result, SP1SXGProofFixture, SXG_ELF},
+    keys::{KeyCache, VkeyArg},
+};
+
+/// The length of a groth16 proof's on-chain bytes: a 4-byte verifier selector and 8 words.
+const GROTH16_PROOF_BYTES: usize = 4 + 8 * 32;
+
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Convert a `-fixture.json` or `-proof-with-io.json` file into `<name>.sxgproof`.
+    Convert {
+        /// The fixture or proof to convert.
+        #[clap(value_parser)]
+        path: PathBuf,
+
+        /// The proof type of a fixture, which the fixture does not record. Guessed from the
+        /// proof's length when omitted.
+        #[clap(long, value_enum)]
+        proof_type: Option<ProofType>,
+
+        /// Verifying key to check a proof-with-io file against, as for `verify-proof`. The
+        /// cached key of the embedded program is used otherwise.
+        #[clap(long)]
+        vkey: Option<VkeyArg>,
+
+        /// Name of the bundle file, without the extension. Defaults to the converted file's name.
+        #[clap(long)]
+        name: Option<String>,
+    },
+    /// Print a bundle.
+    Inspect {
+        #[clap(value_parser)]
+        path: PathBuf,
+    },
+}
+
+/// The name of the bundle converted from `path`, without the suffix `sxg` gave the original.
+fn bundle_name(path: &std::path::Path) -> String {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    ["-proof-with-io.json", "-fixture.json", ".json"]
+        .iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(&file_name)
+        .to_string()
+}
+
+fn convert(
+    path: &std::path::Path,
+    proof_type: Option<ProofType>,
+    vkey: Option<&VkeyArg>,
+    keys: &KeyCache,
+) -> Result<ProofBundle> {
+    let bytes = fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    if let Ok(fixture) = serde_json::from_slice::<SP1SXGProofFixture>(&bytes) {
+        let proof_type = match proof_type {
+            Some(proof_type) => proof_type,
+            None => {
+                let proof_len = fixture.proof.trim_start_matches("0x").len() / 2;
+                match proof_len {
+                    0 => {
+                        return Err(
+                            "a mock fixture's proof type cannot be guessed; pass --proof-type"
+                                .into(),
+                        )
+                    }
+                    GROTH16_PROOF_BYTES => ProofType::Groth16,
+                    _ => ProofType::Plonk,
+                }
+            }
+        };
+        return ProofBundle::from_fixture(&fixture, proof_type);
+    }
+
+    let proof = SP1ProofWithPublicValues::load(path).map_err(|e| {
+        format!(
+            "{} is neither a fixture nor a saved proof: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let vk = keys.resolve_verifying_key(vkey)?;
+    // Only a key read from a file may belong to a different build of the program.
+    let elf = (!matches!(vkey, Some(VkeyArg::Path(_)))).then_some(SXG_ELF);
+    ProverClient::new()
+        .verify(&proof, &vk)
+        .map_err(|e| format!("proof does not verify against {}: {}", vk.bytes32(), e))?;
+    ProofBundle::new(&proof, &vk, elf)
+}
+
+fn print(bundle: &ProofBundle) {
+    println!("Format: {} v{}", bundle.format, bundle.version);
+    println!("Proof Type: {:?}", bundle.proof_type);
+    println!(
+        "SP1 Version: {}",
+        bundle.sp1_version.as_deref().unwrap_or("unknown")
+    );
+    println!("Verification Key: {}", bundle.vkey);
+    println!(
+        "ELF SHA-256: {}",
+        bundle.elf_sha256.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "Input SHA-256: {}",
+        bundle.input_sha256.as_deref().unwrap_or("unknown")
+    );
+    match &bundle.decoded {
+        Some(decoded) => decoded.print(),
+        None => println!("Public Values: {}", bundle.public_values),
+    }
+    let contents: Vec<&str> = [
+        (bundle.proof_with_io.is_some(), "full proof"),
+        (bundle.proof.is_some(), "on-chain proof bytes"),
+    ]
+    .into_iter()
+    .filter_map(|(present, what)| present.then_some(what))
+    .collect();
+    println!("Contains: {}", contents.join(", "));
+    println!(
+        "Created: {} by {}",
+        crate::common::rfc3339(bundle.created_at),
+        bundle.created_by
+    );
+}
+
+pub fn run(command: &BundleCommand, keys: &KeyCache, output: &OutputArgs) -> Result<()> {
+    match command {
+        BundleCommand::Convert {
+            path,
+            proof_type,
+            vkey,
+            name,
+        } => {
+            let bundle = convert(path, *proof_type, vkey.as_ref(), keys)?;
+            let name = name.clone().unwrap_or_else(|| bundle_name(path));
+            let bundle_path = output.path(&format!("{}.{}", name, BUNDLE_EXTENSION))?;
+            bundle.write(&bundle_path)?;
+            output.emit(&bundle, |b| {
+                print(b);
+                println!("Bundle: {}", bundle_path.display());
+            })
+        }
+        BundleCommand::Inspect { path } => output.emit(&ProofBundle::read(path)?, print),
+    }
+}
@@ -0,0 +1,87 @@
+//! `sxg cache`: lists or evicts the proofs in the proof cache.
+
+use std::time::Duration;
+
+use clap::Subcommand;
+use serde::Serialize;
+use sxg_script::proof_cache::{CacheEntry, Eviction, ProofCache};
+
+use crate::common::{OutputArgs, Result};
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// List cached proofs, least recently used first.
+    List,
+    /// Remove cached proofs, least recently used first, until the cache fits the given limits.
+    Evict {
+        /// Size to shrink the cache to. Defaults to `--proof-cache-max-bytes`.
+        #[clap(long)]
+        max_bytes: Option<u64>,
+        /// Also remove proofs that have not been used for this many days.
+        #[clap(long)]
+        max_idle_days: Option<u64>,
+        /// Remove every cached proof.
+        #[clap(long, conflicts_with_all = ["max_bytes", "max_idle_days"])]
+        all: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct Listing {
+    cache_dir: String,
+    total_bytes: u64,
+    max_bytes: u64,
+    entries: Vec<CacheEntry>,
+}
+
+pub fn run(command: &CacheCommand, cache: &ProofCache, output: &OutputArgs) -> Result<()> {
+    match command {
+        CacheCommand::List => {
+            let entries = cache.entries()?;
+            let listing = Listing {
+                cache_dir: cache.proof_cache_dir.display().to_string(),
+                total_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+                max_bytes: cache.proof_cache_max_bytes,
+                entries,
+            };
+            output.emit(&listing, |l| {
+                for entry in &l.entries {
+                    println!(
+                        "{}  {:>12} bytes  idle {}s",
+                        entry.key, entry.bytes, entry.idle_secs
+                    );
+                }
+                println!(
+                    "{} proofs, {} of {} bytes in {}",
+                    l.entries.len(),
+                    l.total_bytes,
+                    l.max_bytes,
+                    l.cache_dir
+                );
+            })
+        }
+        CacheCommand::Evict {
+            max_bytes,
+            max_idle_days,
+            all,
+        } => {
+            let eviction = if *all {
+                cache.evict(0, None)?
+            } else {
+                cache.evict(
+                    max_bytes.unwrap_or(cache.proof_cache_max_bytes),
+                    max_idle_days.map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+                )?
+            };
+            output.emit(&eviction, |e: &Eviction| {
+                for entry in &e.removed {
+                    println!("Removed {} ({} bytes)", entry.key, entry.bytes);
+                }
+                println!(
+                    "Freed {} bytes; {} proofs, {} bytes remain",
+                    e.freed_bytes, e.remaining_entries, e.remaining_bytes
+                );
+            })
+        }
+    }
+}
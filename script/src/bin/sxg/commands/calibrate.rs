@@ -0,0 +1,104 @@
+//! `sxg calibrate`: refits the cycle model from executions of real inputs.
+//!
+//! Each input is executed in the zkVM, and the model's costs are fitted by least squares to the
+//! cycle counts the execution reports give. The model is only as good as the inputs' spread, so
+//! pick inputs with different payload and disclosure sizes.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1Stdin};
+use sxg_script::estimate::{CycleModel, InputFeatures, Sample};
+
+use crate::common::{write_json, InputArgs, OutputArgs, Result, SXG_ELF};
+
+#[derive(Args, Debug)]
+pub struct CalibrateArgs {
+    /// Paths to the SXG input files to execute. At least three are needed.
+    #[clap(value_parser, required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// File name, in `--output-dir`, the fitted model is written to.
+    #[clap(long, default_value = "cycle-model.json")]
+    pub model_file: String,
+}
+
+#[derive(Serialize)]
+struct Fitted {
+    input: String,
+    features: InputFeatures,
+    cycles: u64,
+    estimated_cycles: u64,
+}
+
+#[derive(Serialize)]
+struct Calibration {
+    model: CycleModel,
+    model_path: String,
+    samples: Vec<Fitted>,
+}
+
+pub fn run(args: &CalibrateArgs, output: &OutputArgs) -> Result<()> {
+    let client = ProverClient::new();
+
+    let mut samples = Vec::with_capacity(args.inputs.len());
+    for path in &args.inputs {
+        let sxg_input = InputArgs {
+            input: path.clone(),
+        }
+        .load()?;
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&sxg_input);
+        tracing::info!("executing {}", path.display());
+        let (_, report) = client
+            .execute(SXG_ELF, stdin)
+            .run()
+            .map_err(|e| format!("failed to execute {}: {}", path.display(), e))?;
+
+        samples.push(Sample {
+            features: InputFeatures::of(&sxg_input),
+            cycles: report.total_instruction_count(),
+        });
+    }
+
+    let model = CycleModel::fit(&samples)?;
+    let model_path = output.path(&args.model_file)?;
+    write_json(&model_path, &model)?;
+
+    let calibration = Calibration {
+        samples: args
+            .inputs
+            .iter()
+            .zip(&samples)
+            .map(|(path, sample)| Fitted {
+                input: path.display().to_string(),
+                features: sample.features,
+                cycles: sample.cycles,
+                estimated_cycles: model.estimate(&sample.features),
+            })
+            .collect(),
+        model,
+        model_path: model_path.display().to_string(),
+    };
+
+    output.emit(&calibration, |c| {
+        println!("Cycles per hashed byte: {:.1}", c.model.per_hashed_byte);
+        println!(
+            "Cycles per ECDSA verification: {:.0}",
+            c.model.per_ecdsa_verification
+        );
+        println!(
+            "Cycles per committed byte: {:.1}",
+            c.model.per_committed_byte
+        );
+        for sample in &c.samples {
+            println!(
+                "{:<32} {:>12} cycles, estimated {:>12}",
+                sample.input, sample.cycles, sample.estimated_cycles
+            );
+        }
+        println!("Model: {}", c.model_path);
+    })
+}
@@ -0,0 +1,298 @@
+//! `sxg dump`: pretty-prints an input file or a raw `.sxg` exchange.
+
+use std::{fs, ops::Range, path::PathBuf};
+
+use clap::Args;
+use lib::{
+    der::decode_ecdsa_signature,
+    exchange::{decode_mi_sha256, Exchange, MAGIC},
+    message::SignedMessage,
+    schema::load_input,
+    sxg::{mi_sha256_digest_with_record_size, mi_sha256_record_count, MI_SHA256_RECORD_SIZE},
+    SignatureAlgorithm,
+};
+use serde::Serialize;
+
+use crate::common::{rfc3339, OutputArgs, Result};
+
+#[derive(Args, Debug)]
+pub struct DumpArgs {
+    /// Path to an SXG input file or a raw `application/signed-exchange` file.
+    #[clap(value_parser)]
+    pub file: PathBuf,
+
+    /// Bytes of payload to show on each side of the disclosure.
+    #[clap(long, default_value_t = 64)]
+    pub context: usize,
+}
+
+#[derive(Serialize)]
+struct Dump {
+    source: &'static str,
+    request_url: String,
+    signatures: Vec<SignatureDump>,
+    validity_url: String,
+    cert_sha256: Option<String>,
+    date: Timestamp,
+    expires: Timestamp,
+    headers: Vec<HeaderDump>,
+    payload_len: usize,
+    record_size: usize,
+    record_count: usize,
+    computed_digest: String,
+    signed_digest: Option<String>,
+    digest_matches: bool,
+    disclosure: Option<DisclosureDump>,
+}
+
+#[derive(Serialize, Default)]
+struct SignatureDump {
+    label: Option<String>,
+    algorithm: Option<SignatureAlgorithm>,
+    integrity: Option<String>,
+    cert_url: Option<String>,
+    sig: Option<String>,
+    r: Option<String>,
+    s: Option<String>,
+    public_key: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Timestamp {
+    unix: u64,
+    rfc3339: String,
+}
+
+impl From<u64> for Timestamp {
+    fn from(unix: u64) -> Self {
+        Timestamp {
+            unix,
+            rfc3339: rfc3339(unix),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HeaderDump {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct DisclosureDump {
+    start: usize,
+    len: usize,
+    matches_payload: bool,
+    before: String,
+    text: String,
+    after: String,
+}
+
+/// The parts of a file that a dump is built from, whichever format it is in.
+struct Parts {
+    source: &'static str,
+    final_payload: Vec<u8>,
+    payload: Vec<u8>,
+    record_size: usize,
+    signatures: Vec<SignatureDump>,
+    disclosure: Option<(Range<usize>, Vec<u8>)>,
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn input_parts(json: &str) -> Result<Parts> {
+    let input = load_input(json)?;
+    let (r, s) = input.signature()?;
+    let signature = SignatureDump {
+        algorithm: Some(input.algorithm),
+        sig: input.sig.as_deref().map(hex_string),
+        r: Some(hex_string(&r)),
+        s: Some(hex_string(&s)),
+        public_key: input.public_key().ok().as_deref().map(hex_string),
+        ..Default::default()
+    };
+    let start = input.data_to_verify_start_index;
+
+    Ok(Parts {
+        source: "input",
+        final_payload: input.final_payload,
+        payload: input.payload,
+        record_size: MI_SHA256_RECORD_SIZE,
+        signatures: vec![signature],
+        disclosure: Some((
+            start..start + input.data_to_verify.len(),
+            input.data_to_verify,
+        )),
+    })
+}
+
+fn exchange_parts(bytes: &[u8]) -> Result<Parts> {
+    let exchange = Exchange::parse(bytes)?;
+    let params = exchange.signatures()?;
+    let (record_size, payload) = decode_mi_sha256(exchange.payload)?;
+
+    let signatures = params
+        .iter()
+        .map(|params| {
+            let r_s = [32, 48]
+                .into_iter()
+                .find_map(|field_size| decode_ecdsa_signature(&params.sig, field_size).ok());
+            SignatureDump {
+                label: Some(params.label.clone()),
+                integrity: Some(params.integrity.clone()),
+                cert_url: params.cert_url.clone(),
+                sig: Some(hex_string(&params.sig)),
+                r: r_s.as_ref().map(|(r, _)| hex_string(r)),
+                s: r_s.as_ref().map(|(_, s)| hex_string(s)),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(Parts {
+        source: "exchange",
+        final_payload: exchange.signed_message(&params[0]),
+        payload,
+        record_size,
+        signatures,
+        disclosure: None,
+    })
+}
+
+fn dump(parts: Parts, context: usize) -> Result<Dump> {
+    let message = SignedMessage::parse(&parts.final_payload)?;
+    let headers = message.headers()?;
+    let signed_digest = message.header("digest")?.map(lossy);
+    let computed_digest = lossy(&mi_sha256_digest_with_record_size(
+        &parts.payload,
+        parts.record_size,
+    ));
+
+    let disclosure = parts.disclosure.map(|(range, text)| {
+        let payload = &parts.payload;
+        let window = |from: usize, to: usize| {
+            let clamp = |i: usize| i.min(payload.len());
+            lossy(&payload[clamp(from)..clamp(to)])
+        };
+        DisclosureDump {
+            start: range.start,
+            len: range.len(),
+            matches_payload: payload.get(range.clone()) == Some(&text[..]),
+            before: window(range.start.saturating_sub(context), range.start),
+            text: lossy(&text),
+            after: window(range.end, range.end.saturating_add(context)),
+        }
+    });
+
+    Ok(Dump {
+        source: parts.source,
+        request_url: lossy(message.request_url),
+        signatures: parts.signatures,
+        validity_url: lossy(message.validity_url),
+        cert_sha256: message.cert_sha256.map(hex_string),
+        date: message.date.into(),
+        expires: message.expires.into(),
+        headers: headers
+            .into_iter()
+            .map(|(name, value)| HeaderDump {
+                name: lossy(name),
+                value: lossy(value),
+            })
+            .collect(),
+        payload_len: parts.payload.len(),
+        record_size: parts.record_size,
+        record_count: mi_sha256_record_count(parts.payload.len(), parts.record_size),
+        digest_matches: signed_digest.as_ref() == Some(&computed_digest),
+        computed_digest,
+        signed_digest,
+        disclosure,
+    })
+}
+
+fn print(d: &Dump) {
+    println!("Source: {}", d.source);
+    println!("Request URL: {}", d.request_url);
+    for signature in &d.signatures {
+        println!(
+            "Signature {}:",
+            signature.label.as_deref().unwrap_or("(from input)")
+        );
+        let fields = [
+            ("algorithm", signature.algorithm.map(|a| format!("{:?}", a))),
+            ("integrity", signature.integrity.clone()),
+            ("cert-url", signature.cert_url.clone()),
+            ("sig", signature.sig.clone()),
+            ("r", signature.r.clone()),
+            ("s", signature.s.clone()),
+            ("public key", signature.public_key.clone()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                println!("  {:<11} {}", name, value);
+            }
+        }
+    }
+    println!("Validity URL: {}", d.validity_url);
+    if let Some(cert_sha256) = &d.cert_sha256 {
+        println!("Cert SHA-256: {}", cert_sha256);
+    }
+    println!("Date: {} ({})", d.date.rfc3339, d.date.unix);
+    println!("Expires: {} ({})", d.expires.rfc3339, d.expires.unix);
+
+    println!("Response headers:");
+    let width = d.headers.iter().map(|h| h.name.len()).max().unwrap_or(0);
+    for header in &d.headers {
+        println!("  {:<width$}  {}", header.name, header.value, width = width);
+    }
+
+    println!(
+        "Payload: {} bytes in {} records of {} bytes",
+        d.payload_len, d.record_count, d.record_size
+    );
+    println!("Computed digest: {}", d.computed_digest);
+    println!(
+        "Signed digest:   {} ({})",
+        d.signed_digest.as_deref().unwrap_or("(no digest header)"),
+        if d.digest_matches {
+            "match"
+        } else {
+            "MISMATCH"
+        }
+    );
+
+    if let Some(disclosure) = &d.disclosure {
+        println!(
+            "Disclosure: {} bytes at {} ({})",
+            disclosure.len,
+            disclosure.start,
+            if disclosure.matches_payload {
+                "matches payload"
+            } else {
+                "DOES NOT MATCH payload"
+            }
+        );
+        println!(
+            "  …{:?}[[{:?}]]{:?}…",
+            disclosure.before, disclosure.text, disclosure.after
+        );
+    }
+}
+
+pub fn run(args: &DumpArgs, output: &OutputArgs) -> Result<()> {
+    let bytes = fs::read(&args.file)
+        .map_err(|e| format!("failed to read {}: {}", args.file.display(), e))?;
+
+    let parts = if bytes.starts_with(MAGIC) {
+        exchange_parts(&bytes)?
+    } else {
+        input_parts(std::str::from_utf8(&bytes)?)?
+    };
+
+    output.emit(&dump(parts, args.context)?, print)
+}
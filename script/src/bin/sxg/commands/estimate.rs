@@ -0,0 +1,62 @@
+//! `sxg estimate`: predicts an input's cycle count without executing it.
+
+use clap::Args;
+use serde::Serialize;
+use sxg_script::estimate::InputFeatures;
+
+use crate::{
+    budget::BudgetArgs,
+    common::{InputArgs, OutputArgs, Result},
+};
+
+#[derive(Args, Debug)]
+pub struct EstimateArgs {
+    #[clap(flatten)]
+    pub input: InputArgs,
+
+    #[clap(flatten)]
+    pub budget: BudgetArgs,
+}
+
+#[derive(Serialize)]
+struct Estimate {
+    features: InputFeatures,
+    estimated_cycles: u64,
+    cycle_budget: Option<u64>,
+    calibrated: bool,
+}
+
+pub fn run(args: &EstimateArgs, output: &OutputArgs) -> Result<()> {
+    let sxg_input = args.input.load()?;
+    let model = args.budget.model()?;
+    let features = InputFeatures::of(&sxg_input);
+
+    let estimate = Estimate {
+        features,
+        estimated_cycles: model.estimate(&features),
+        cycle_budget: args.budget.cycle_budget,
+        calibrated: model.samples > 0,
+    };
+
+    output.emit(&estimate, |e| {
+        println!("Hashed bytes: {}", e.features.hashed_bytes);
+        println!("ECDSA verifications: {}", e.features.ecdsa_verifications);
+        println!("Committed bytes: {}", e.features.committed_bytes);
+        println!(
+            "Estimated cycles: {}{}",
+            e.estimated_cycles,
+            if e.calibrated {
+                ""
+            } else {
+                " (uncalibrated model)"
+            }
+        );
+        if let Some(budget) = e.cycle_budget {
+            println!("Cycle budget: {}", budget);
+        }
+    })?;
+
+    args.budget
+        .check(&model, &features, &args.input.input.display().to_string())?;
+    Ok(())
+}
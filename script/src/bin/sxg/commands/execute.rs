@@ -0,0 +1,38 @@
+//! `sxg execute`: runs the guest in the zkVM without proving.
+
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+use crate::common::{DecodedPublicValues, InputArgs, OutputArgs, Result, SXG_ELF};
+
+#[derive(Serialize)]
+struct Execution {
+    public_values: DecodedPublicValues,
+    cycles: u64,
+}
+
+pub fn run(args: &InputArgs, output: &OutputArgs) -> Result<()> {
+    let sxg_input = args.load()?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&sxg_input);
+
+    let client = ProverClient::new();
+    let (public_values, report) = client.execute(SXG_ELF, stdin).run()?;
+
+    let execution = Execution {
+        public_values: DecodedPublicValues::decode(public_values.as_slice())?,
+        cycles: report.total_instruction_count(),
+    };
+
+    output.emit(&execution, |e| {
+        println!("Program executed successfully.");
+        e.public_values.print();
+        println!("Number of cycles: {}", e.cycles);
+    })?;
+
+    if execution.public_values.result != 1 {
+        return Err("SXG verification failed inside the zkVM".into());
+    }
+    Ok(())
+}
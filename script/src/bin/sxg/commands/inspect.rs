@@ -0,0 +1,59 @@
+//! `sxg inspect`: decodes an input file without verifying or proving it.
+
+use lib::{sxg::mi_sha256_digest, LowSPolicy, SignatureAlgorithm};
+use serde::Serialize;
+
+use crate::common::{InputArgs, OutputArgs, Result};
+
+#[derive(Serialize)]
+struct Inspection {
+    algorithm: SignatureAlgorithm,
+    low_s: LowSPolicy,
+    final_payload_len: usize,
+    payload_len: usize,
+    integrity_start_index: usize,
+    data_to_verify_start_index: usize,
+    data_to_verify: String,
+    mi_sha256: String,
+    signature_form: &'static str,
+    r: String,
+    s: String,
+    public_key: String,
+}
+
+pub fn run(args: &InputArgs, output: &OutputArgs) -> Result<()> {
+    let sxg_input = args.load()?;
+    let (r, s) = sxg_input.signature()?;
+
+    let inspection = Inspection {
+        algorithm: sxg_input.algorithm,
+        low_s: sxg_input.low_s,
+        final_payload_len: sxg_input.final_payload.len(),
+        payload_len: sxg_input.payload.len(),
+        integrity_start_index: sxg_input.integrity_start_index,
+        data_to_verify_start_index: sxg_input.data_to_verify_start_index,
+        data_to_verify: String::from_utf8_lossy(&sxg_input.data_to_verify).into_owned(),
+        mi_sha256: String::from_utf8_lossy(&mi_sha256_digest(&sxg_input.payload)).into_owned(),
+        signature_form: if sxg_input.sig.is_some() {
+            "der"
+        } else {
+            "raw"
+        },
+        r: hex::encode(r),
+        s: hex::encode(s),
+        public_key: hex::encode(sxg_input.public_key()?),
+    };
+
+    output.emit(&inspection, |i| {
+        println!("Algorithm: {:?}", i.algorithm);
+        println!("Low-S policy: {:?}", i.low_s);
+        println!("Signed message: {} bytes", i.final_payload_len);
+        println!("Payload: {} bytes", i.payload_len);
+        println!("Integrity start index: {}", i.integrity_start_index);
+        println!("Disclosure start index: {}", i.data_to_verify_start_index);
+        println!("Disclosure: {:?}", i.data_to_verify);
+        println!("Computed digest: {}", i.mi_sha256);
+        println!("Signature ({}): r={} s={}", i.signature_form, i.r, i.s);
+        println!("Public key: {}", i.public_key);
+    })
+}
@@ -0,0 +1,63 @@
+//! `sxg keys`: exports the cached proving and verifying keys, or prints the vkey hash.
+
+use std::fs;
+
+use clap::Subcommand;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient};
+
+use crate::{
+    common::{OutputArgs, Result, SXG_ELF},
+    keys::{elf_sha256, KeyCache, PK_FILE, VKEY_FILE, VK_FILE},
+};
+
+#[derive(Subcommand, Debug)]
+pub enum KeysCommand {
+    /// Copy `pk.bin`, `vk.bin` and `vkey.json` into `--output-dir`, running setup if needed.
+    Export,
+    /// Print the verification key hash that the on-chain verifier pins the program to.
+    ShowVkey,
+}
+
+#[derive(Serialize)]
+struct KeyInfo {
+    elf_sha256: String,
+    vkey: String,
+    cache_dir: String,
+    exported: Vec<String>,
+}
+
+pub fn run(command: &KeysCommand, keys: &KeyCache, output: &OutputArgs) -> Result<()> {
+    let (vk, exported) = match command {
+        KeysCommand::Export => {
+            let (_, vk) = keys.keys(&ProverClient::new())?;
+            let mut exported = Vec::new();
+            for file in [PK_FILE, VK_FILE, VKEY_FILE] {
+                let path = output.path(file)?;
+                fs::copy(keys.dir().join(file), &path)
+                    .map_err(|e| format!("failed to export {}: {}", path.display(), e))?;
+                exported.push(path.display().to_string());
+            }
+            (vk, exported)
+        }
+        KeysCommand::ShowVkey => (keys.verifying_key()?, Vec::new()),
+    };
+
+    let info = KeyInfo {
+        elf_sha256: elf_sha256(SXG_ELF),
+        vkey: vk.bytes32(),
+        cache_dir: keys.dir().display().to_string(),
+        exported,
+    };
+
+    output.emit(&info, |i| match command {
+        KeysCommand::ShowVkey => println!("{}", i.vkey),
+        KeysCommand::Export => {
+            println!("ELF SHA-256: {}", i.elf_sha256);
+            println!("Verification Key: {}", i.vkey);
+            for path in &i.exported {
+                println!("Exported: {}", path);
+            }
+        }
+    })
+}
@@ -0,0 +1,16 @@
+pub mod aggregate;
+pub mod baseline;
+pub mod batch;
+pub mod bundle;
+pub mod cache;
+pub mod calibrate;
+pub mod dump;
+pub mod estimate;
+pub mod execute;
+pub mod inspect;
+pub mod keys;
+pub mod profile;
+pub mod prove;
+pub mod prove_multi;
+pub mod verify_native;
+pub mod verify_proof;
@@ -0,0 +1,76 @@
+//! `sxg profile`: executes the guest and breaks its cycle count down by stage.
+//!
+//! The stages are the ones the guest brackets with `lib::track_cycles!`. Cycles spent outside
+//! every stage, such as committing the public values, are listed as `other`.
+
+use lib::CYCLE_STAGES;
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+use crate::common::{InputArgs, OutputArgs, Result, SXG_ELF};
+
+#[derive(Serialize)]
+struct Stage {
+    name: String,
+    cycles: u64,
+    percent: f64,
+}
+
+#[derive(Serialize)]
+struct Profile {
+    total_cycles: u64,
+    stages: Vec<Stage>,
+}
+
+pub fn run(args: &InputArgs, output: &OutputArgs) -> Result<()> {
+    let sxg_input = args.load()?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&sxg_input);
+
+    let client = ProverClient::new();
+    let (_, report) = client.execute(SXG_ELF, stdin).run()?;
+    let total_cycles = report.total_instruction_count();
+
+    // Known stages in the order they run, then anything a newer guest tracks that we don't know.
+    let mut tracked: Vec<(String, u64)> = CYCLE_STAGES
+        .iter()
+        .filter_map(|&name| Some((name.to_string(), *report.cycle_tracker.get(name)?)))
+        .collect();
+    let mut unknown: Vec<(String, u64)> = report
+        .cycle_tracker
+        .iter()
+        .filter(|(name, _)| !CYCLE_STAGES.contains(&name.as_str()))
+        .map(|(name, &cycles)| (name.clone(), cycles))
+        .collect();
+    unknown.sort();
+    tracked.extend(unknown);
+
+    let tracked_cycles: u64 = tracked.iter().map(|(_, cycles)| cycles).sum();
+    tracked.push((
+        "other".to_string(),
+        total_cycles.saturating_sub(tracked_cycles),
+    ));
+
+    let profile = Profile {
+        total_cycles,
+        stages: tracked
+            .into_iter()
+            .map(|(name, cycles)| Stage {
+                name,
+                cycles,
+                percent: 100.0 * cycles as f64 / total_cycles.max(1) as f64,
+            })
+            .collect(),
+    };
+
+    output.emit(&profile, |p| {
+        for stage in &p.stages {
+            println!(
+                "{:<16} {:>12} cycles {:>6.1}%",
+                stage.name, stage.cycles, stage.percent
+            );
+        }
+        println!("{:<16} {:>12} cycles", "total", p.total_cycles);
+    })
+}
@@ -0,0 +1,141 @@
+//! `sxg prove`: generates a proof, saves it, and writes a Solidity fixture for EVM proof types.
+
+use clap::Args;
+use lib::sxg::SXGInput;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProvingKey, SP1VerifyingKey};
+use sxg_script::{
+    bundle::{ProofBundle, BUNDLE_EXTENSION},
+    estimate::InputFeatures,
+    proof_cache::ProofCache,
+};
+
+use crate::{
+    budget::BudgetArgs,
+    common::{
+        write_json, DecodedPublicValues, InputArgs, OutputArgs, ProofType, Result,
+        SP1SXGProofFixture, SXG_ELF,
+    },
+    keys::KeyCache,
+};
+
+#[derive(Args, Debug)]
+pub struct ProveArgs {
+    #[clap(flatten)]
+    pub input: InputArgs,
+
+    /// The kind of proof to generate. Only groth16 and plonk proofs can be verified on-chain.
+    #[clap(long, value_enum, default_value = "core")]
+    pub proof_type: ProofType,
+
+    #[clap(flatten)]
+    pub budget: BudgetArgs,
+}
+
+/// What proving one input produced.
+#[derive(Serialize)]
+pub struct Proved {
+    pub proof_type: ProofType,
+    pub vkey: String,
+    pub public_values: DecodedPublicValues,
+    pub proof_path: String,
+    pub fixture_path: Option<String>,
+    pub bundle_path: String,
+    /// Whether the proof came from the proof cache instead of being generated.
+    pub cached: bool,
+}
+
+impl Proved {
+    pub fn print(&self) {
+        if self.cached {
+            println!("Reused and verified cached {:?} proof!", self.proof_type);
+        } else {
+            println!(
+                "Successfully generated and verified {:?} proof!",
+                self.proof_type
+            );
+        }
+        self.public_values.print();
+        println!("Verification Key: {}", self.vkey);
+        println!("Proof: {}", self.proof_path);
+        if let Some(fixture_path) = &self.fixture_path {
+            println!("Fixture: {}", fixture_path);
+        }
+        println!("Bundle: {}", self.bundle_path);
+    }
+}
+
+/// Proves `sxg_input`, or takes its proof from `cache`, verifies the proof and writes
+/// `{stem}-proof-with-io.json`, `{stem}-fixture.json` for EVM proof types and the
+/// `{stem}.sxgproof` bundle.
+pub fn prove_input(
+    client: &ProverClient,
+    (pk, vk): (&SP1ProvingKey, &SP1VerifyingKey),
+    cache: &ProofCache,
+    sxg_input: &SXGInput,
+    stem: &str,
+    proof_type: ProofType,
+    output: &OutputArgs,
+) -> Result<Proved> {
+    let (proof, cached) = cache.prove(client, (pk, vk), sxg_input, proof_type)?;
+
+    let proof_path = output.path(&format!("{}-proof-with-io.json", stem))?;
+    proof.save(&proof_path)?;
+
+    let public_values = DecodedPublicValues::decode(proof.public_values.as_slice())?;
+
+    // The verification key stays the same regardless of the input, and is what the on-chain
+    // verifier pins the program to.
+    let vkey = vk.bytes32();
+
+    let fixture_path = if proof_type.is_evm() {
+        let fixture = SP1SXGProofFixture::new(public_values.result, vk, &proof);
+        let path = output.path(&format!("{}-fixture.json", stem))?;
+        write_json(&path, &fixture)?;
+        Some(path.display().to_string())
+    } else {
+        None
+    };
+
+    let bundle_path = output.path(&format!("{}.{}", stem, BUNDLE_EXTENSION))?;
+    ProofBundle::new(&proof, vk, Some(SXG_ELF))?.write(&bundle_path)?;
+
+    Ok(Proved {
+        proof_type,
+        vkey,
+        public_values,
+        proof_path: proof_path.display().to_string(),
+        fixture_path,
+        bundle_path: bundle_path.display().to_string(),
+        cached,
+    })
+}
+
+pub fn run(
+    args: &ProveArgs,
+    keys: &KeyCache,
+    cache: &ProofCache,
+    output: &OutputArgs,
+) -> Result<()> {
+    let sxg_input = args.input.load()?;
+    args.budget.check(
+        &args.budget.model()?,
+        &InputFeatures::of(&sxg_input),
+        &args.input.input.display().to_string(),
+    )?;
+
+    let client = ProverClient::new();
+    let (pk, vk) = keys.keys(&client)?;
+
+    let proved = prove_input(
+        &client,
+        (&pk, &vk),
+        cache,
+        &sxg_input,
+        &args.input.stem(),
+        args.proof_type,
+        output,
+    )?;
+
+    output.emit(&proved, Proved::print)
+}
@@ -0,0 +1,124 @@
+//! `sxg prove-multi`: proves several inputs in one execution of the batch program.
+//!
+//! The result is a single proof whose public values list every exchange plus their combined
+//! hash, so one on-chain verification covers all of them.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1Stdin};
+use sxg_script::estimate::InputFeatures;
+
+use crate::{
+    budget::BudgetArgs,
+    common::{
+        write_json, DecodedBatchPublicValues, InputArgs, OutputArgs, ProofType, Result,
+        SP1SXGProofFixture, SXG_BATCH_ELF,
+    },
+    keys::KeyCache,
+};
+
+#[derive(Args, Debug)]
+pub struct ProveMultiArgs {
+    /// Paths to the SXG input files, in the order they are committed.
+    #[clap(value_parser, required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// The kind of proof to generate. Only groth16 and plonk proofs can be verified on-chain.
+    #[clap(long, value_enum, default_value = "core")]
+    pub proof_type: ProofType,
+
+    /// Name the proof and fixture files are written under.
+    #[clap(long, default_value = "multi")]
+    pub name: String,
+
+    #[clap(flatten)]
+    pub budget: BudgetArgs,
+}
+
+#[derive(Serialize)]
+struct ProvedMulti {
+    proof_type: ProofType,
+    vkey: String,
+    public_values: DecodedBatchPublicValues,
+    proof_path: String,
+    fixture_path: Option<String>,
+}
+
+pub fn run(args: &ProveMultiArgs, keys: &KeyCache, output: &OutputArgs) -> Result<()> {
+    let sxg_inputs = args
+        .inputs
+        .iter()
+        .map(|input| InputArgs {
+            input: input.clone(),
+        })
+        .map(|input| input.load())
+        .collect::<Result<Vec<_>>>()?;
+
+    // The batch program does the work of each input in turn, so its cost is roughly the sum.
+    let features = sxg_inputs
+        .iter()
+        .map(InputFeatures::of)
+        .fold(InputFeatures::default(), |total, f| total + f);
+    args.budget
+        .check(&args.budget.model()?, &features, "the batch")?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&sxg_inputs);
+
+    let client = ProverClient::new();
+    let (pk, vk) = keys.keys_for(&client, SXG_BATCH_ELF)?;
+
+    let builder = client.prove(&pk, stdin);
+    let proof = match args.proof_type {
+        ProofType::Core => builder.core().run(),
+        ProofType::Compressed => builder.compressed().run(),
+        ProofType::Groth16 => builder.groth16().run(),
+        ProofType::Plonk => builder.plonk().run(),
+    }?;
+
+    client.verify(&proof, &vk)?;
+
+    let proof_path = output.path(&format!("{}-proof-with-io.json", args.name))?;
+    proof.save(&proof_path)?;
+
+    let public_values = DecodedBatchPublicValues::decode(proof.public_values.as_slice())?;
+    let vkey = vk.bytes32();
+
+    let fixture_path = if args.proof_type.is_evm() {
+        let fixture = SP1SXGProofFixture::new(public_values.all_verified() as u32, &vk, &proof);
+        let path = output.path(&format!("{}-fixture.json", args.name))?;
+        write_json(&path, &fixture)?;
+        Some(path.display().to_string())
+    } else {
+        None
+    };
+
+    let proved = ProvedMulti {
+        proof_type: args.proof_type,
+        vkey,
+        public_values,
+        proof_path: proof_path.display().to_string(),
+        fixture_path,
+    };
+
+    output.emit(&proved, |p| {
+        println!(
+            "Successfully generated and verified {:?} proof of {} exchanges!",
+            p.proof_type,
+            p.public_values.exchanges.len()
+        );
+        p.public_values.print();
+        println!("Verification Key: {}", p.vkey);
+        println!("Proof: {}", p.proof_path);
+        if let Some(fixture_path) = &p.fixture_path {
+            println!("Fixture: {}", fixture_path);
+        }
+    })?;
+
+    if !proved.public_values.all_verified() {
+        return Err("the proof is valid, but not every exchange verified".into());
+    }
+    Ok(())
+}
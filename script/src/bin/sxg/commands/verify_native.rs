@@ -0,0 +1,80 @@
+//! `sxg verify-native`: runs the SXG checks on the host, without the zkVM, and reports on each.
+
+use lib::report::{CheckStatus, Mismatch, VerificationReport};
+use serde::Serialize;
+
+use crate::common::{InputArgs, OutputArgs, Result};
+
+/// Bytes of `expected` and `actual` shown before they are elided.
+const PREVIEW_LEN: usize = 64;
+
+#[derive(Serialize)]
+struct NativeVerification {
+    verified: bool,
+    data_to_verify: String,
+    report: VerificationReport,
+}
+
+fn preview(bytes: &[u8]) -> String {
+    let shown = String::from_utf8_lossy(&bytes[..bytes.len().min(PREVIEW_LEN)]);
+    if bytes.len() > PREVIEW_LEN {
+        format!("{:?}… ({} bytes)", shown, bytes.len())
+    } else {
+        format!("{:?}", shown)
+    }
+}
+
+fn print_mismatch(mismatch: &Mismatch) {
+    println!("      expected: {}", preview(&mismatch.expected));
+    println!(
+        "      actual:   {} at {} in `{}`",
+        preview(&mismatch.actual),
+        mismatch.offset,
+        mismatch.field
+    );
+    println!(
+        "      first difference at {}; surrounding bytes:",
+        mismatch.first_difference
+    );
+    for line in mismatch.hexdump().lines() {
+        println!("        {}", line);
+    }
+}
+
+fn print(v: &NativeVerification) {
+    for check in &v.report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skipped => "SKIP",
+        };
+        println!("[{}] {}: {}", status, check.name, check.detail);
+        if let Some(mismatch) = &check.mismatch {
+            print_mismatch(mismatch);
+        }
+        if let Some(hint) = &check.hint {
+            println!("      hint: {}", hint);
+        }
+    }
+    println!("SXG verification result: {}", v.verified);
+    println!("Data Verified: {:?}", v.data_to_verify);
+}
+
+pub fn run(args: &InputArgs, output: &OutputArgs) -> Result<()> {
+    let sxg_input = args.load()?;
+    let report = sxg_input.verify_with_report();
+
+    let verification = NativeVerification {
+        verified: report.verified(),
+        data_to_verify: String::from_utf8_lossy(&sxg_input.data_to_verify).into_owned(),
+        report,
+    };
+
+    output.emit(&verification, print)?;
+
+    if !verification.verified {
+        let failed: Vec<_> = verification.report.failures().map(|c| c.name).collect();
+        return Err(format!("SXG verification failed: {}", failed.join(", ")).into());
+    }
+    Ok(())
+}
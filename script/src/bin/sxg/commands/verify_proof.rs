@@ -0,0 +1,62 @@
+//! `sxg verify-proof`: checks a proof saved by `sxg prove` against the SXG program.
+//!
+//! Verification runs offline. `--vkey` takes a `vk.bin` path or the embedded program's vkey hash;
+//! without it, the key cache for the embedded ELF is used. The command fails if the proof does not
+//! verify or if the proven SXG check did not pass.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues};
+
+use crate::{
+    common::{DecodedPublicValues, OutputArgs, Result},
+    keys::{KeyCache, VkeyArg},
+};
+
+#[derive(Args, Debug)]
+pub struct VerifyProofArgs {
+    /// Path to a proof saved by `sxg prove`.
+    #[clap(value_parser)]
+    pub proof: PathBuf,
+
+    /// The verifying key to check the proof against: a path to a bincode-encoded key, such as the
+    /// `vk.bin` written by `sxg keys export`, or a `0x` vkey hash as printed in fixtures. A hash
+    /// only confirms the embedded program's key, since the full key is needed to verify.
+    #[clap(long)]
+    pub vkey: Option<VkeyArg>,
+}
+
+#[derive(Serialize)]
+struct ProofVerification {
+    vkey: String,
+    public_values: DecodedPublicValues,
+}
+
+pub fn run(args: &VerifyProofArgs, keys: &KeyCache, output: &OutputArgs) -> Result<()> {
+    let proof = SP1ProofWithPublicValues::load(&args.proof)
+        .map_err(|e| format!("failed to load {}: {}", args.proof.display(), e))?;
+
+    let vk = keys.resolve_verifying_key(args.vkey.as_ref())?;
+    let client = ProverClient::new();
+    client
+        .verify(&proof, &vk)
+        .map_err(|e| format!("proof does not verify against {}: {}", vk.bytes32(), e))?;
+
+    let verification = ProofVerification {
+        vkey: vk.bytes32(),
+        public_values: DecodedPublicValues::decode(proof.public_values.as_slice())?,
+    };
+
+    output.emit(&verification, |v| {
+        println!("Successfully verified proof!");
+        println!("Verification Key: {}", v.vkey);
+        v.public_values.print();
+    })?;
+
+    if verification.public_values.result != 1 {
+        return Err("the proof is valid, but it attests that the SXG check failed".into());
+    }
+    Ok(())
+}
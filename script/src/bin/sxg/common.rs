@@ -0,0 +1,157 @@
+//! Input loading, output options and public-value decoding shared by every subcommand.
+
+use std::{fs, path::PathBuf};
+
+use alloy_sol_types::SolType;
+use clap::Args;
+use lib::{
+    schema::load_input, sxg::SXGInput, AggregationPublicValuesStruct, BatchPublicValuesStruct,
+};
+use serde::Serialize;
+
+pub use sxg_script::{
+    write_json, DecodedPublicValues, ProofType, Result, SP1AggregationProofFixture,
+    SP1SXGProofFixture, SXG_AGGREGATION_ELF, SXG_BATCH_ELF, SXG_ELF,
+};
+
+/// The SXG input file a command operates on.
+#[derive(Args, Debug)]
+pub struct InputArgs {
+    /// Path to an SXG input file, in any supported version.
+    #[clap(value_parser)]
+    pub input: PathBuf,
+}
+
+impl InputArgs {
+    pub fn load(&self) -> Result<SXGInput> {
+        let file_content = fs::read_to_string(&self.input)
+            .map_err(|e| format!("failed to read {}: {}", self.input.display(), e))?;
+        Ok(load_input(&file_content)?)
+    }
+
+    /// The input file name without its extension, used to name the files a command writes.
+    pub fn stem(&self) -> String {
+        self.input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "sxg".to_string())
+    }
+}
+
+/// Options controlling where and how results are written.
+#[derive(Args, Debug)]
+pub struct OutputArgs {
+    /// Directory that proofs and fixtures are written to.
+    #[clap(long, global = true, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// Print results as JSON instead of human-readable text.
+    #[clap(long, global = true)]
+    pub json: bool,
+}
+
+impl OutputArgs {
+    pub fn path(&self, file_name: &str) -> Result<PathBuf> {
+        fs::create_dir_all(&self.output_dir)?;
+        Ok(self.output_dir.join(file_name))
+    }
+
+    /// Prints `value` as JSON in `--json` mode, and runs `human` otherwise.
+    pub fn emit<T: Serialize>(&self, value: &T, human: impl FnOnce(&T)) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(value)?);
+        } else {
+            human(value);
+        }
+        Ok(())
+    }
+}
+
+/// The committed [`BatchPublicValuesStruct`], decoded for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedBatchPublicValues {
+    pub exchanges: Vec<DecodedPublicValues>,
+    pub combined_hash: String,
+}
+
+impl DecodedBatchPublicValues {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let BatchPublicValuesStruct {
+            exchanges,
+            combined_hash,
+        } = BatchPublicValuesStruct::abi_decode(bytes, true)?;
+
+        Ok(DecodedBatchPublicValues {
+            exchanges: exchanges.into_iter().map(Into::into).collect(),
+            combined_hash: combined_hash.to_string(),
+        })
+    }
+
+    /// Whether every exchange in the batch verified.
+    pub fn all_verified(&self) -> bool {
+        self.exchanges.iter().all(|exchange| exchange.result == 1)
+    }
+
+    pub fn print(&self) {
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            println!("Exchange {}:", i);
+            exchange.print();
+        }
+        println!("Combined Hash: {}", self.combined_hash);
+    }
+}
+
+/// The committed [`AggregationPublicValuesStruct`], decoded for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedAggregationPublicValues {
+    pub sxg_vkey_hash: String,
+    pub public_values_digests: Vec<String>,
+}
+
+impl DecodedAggregationPublicValues {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let AggregationPublicValuesStruct {
+            sxg_vkey_hash,
+            public_values_digests,
+        } = AggregationPublicValuesStruct::abi_decode(bytes, true)?;
+
+        Ok(DecodedAggregationPublicValues {
+            sxg_vkey_hash: sxg_vkey_hash.to_string(),
+            public_values_digests: public_values_digests
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        })
+    }
+
+    pub fn print(&self) {
+        println!("SXG Verification Key Hash: {}", self.sxg_vkey_hash);
+        for (i, digest) in self.public_values_digests.iter().enumerate() {
+            println!("Public Values Digest {}: {}", i, digest);
+        }
+    }
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 timestamp in UTC.
+pub fn rfc3339(unix: u64) -> String {
+    // Howard Hinnant's `civil_from_days`, for days since 1970-01-01.
+    let (days, seconds) = (unix / 86400, unix % 86400);
+    let z = days + 719468;
+    let (era, doe) = (z / 146097, z % 146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,131 @@
+//! The `sxg` command-line tool: inspect, verify, execute and prove SXG inputs with SP1.
+//!
+//! You can run this script using the following commands:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- verify-native sxg_input_1.json
+//! RUST_LOG=info cargo run --release -- execute sxg_input_1.json
+//! RUST_LOG=info cargo run --release -- prove --proof-type groth16 sxg_input_1.json
+//! ```
+
+mod budget;
+mod commands;
+mod common;
+
+use clap::{Parser, Subcommand};
+
+use commands::{
+    aggregate::AggregateArgs, baseline::BaselineArgs, batch::BatchArgs, bundle::BundleCommand,
+    cache::CacheCommand, calibrate::CalibrateArgs, dump::DumpArgs, estimate::EstimateArgs,
+    keys::KeysCommand, prove::ProveArgs, prove_multi::ProveMultiArgs,
+    verify_proof::VerifyProofArgs,
+};
+use common::{InputArgs, OutputArgs, Result};
+use sxg_script::{
+    keys::{self, KeyCache},
+    proof_cache::ProofCache,
+};
+
+/// Inspect, verify, execute and prove SXG inputs with SP1.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(flatten)]
+    output: OutputArgs,
+
+    #[clap(flatten)]
+    keys: KeyCache,
+
+    #[clap(flatten)]
+    proof_cache: ProofCache,
+
+    /// Use SP1's mock prover, like `SP1_PROVER=mock`: programs still execute, so public values
+    /// are real, but proofs are empty and only pass mock verifiers.
+    #[clap(long, global = true)]
+    mock: bool,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Decode an input file and print its contents.
+    Inspect(InputArgs),
+    /// Pretty-print an input file or a raw `.sxg` exchange for debugging.
+    Dump(DumpArgs),
+    /// Run the SXG checks natively on the host.
+    VerifyNative(InputArgs),
+    /// Execute the program in the zkVM without generating a proof.
+    Execute(InputArgs),
+    /// Execute the program and break its cycle count down by guest stage.
+    Profile(InputArgs),
+    /// Check or update the cycle counts of the bundled inputs against the checked-in baseline.
+    Baseline(BaselineArgs),
+    /// Predict an input's cycle count from the cycle model, without executing it.
+    Estimate(EstimateArgs),
+    /// Refit the cycle model from executions of the given inputs.
+    Calibrate(CalibrateArgs),
+    /// Generate a proof, plus a Solidity fixture for groth16 and plonk proofs.
+    Prove(ProveArgs),
+    /// Prove several inputs together in one execution of the batch program.
+    ProveMulti(ProveMultiArgs),
+    /// Prove every input in a directory or JSONL manifest, writing a summary report.
+    Batch(BatchArgs),
+    /// Aggregate compressed SXG proofs into a single proof.
+    Aggregate(AggregateArgs),
+    /// Verify a saved proof against the SXG program.
+    VerifyProof(VerifyProofArgs),
+    /// Manage the cached proving and verifying keys.
+    Keys {
+        #[clap(subcommand)]
+        command: KeysCommand,
+    },
+    /// Convert fixtures and saved proofs to `.sxgproof` bundles, or print a bundle.
+    Bundle {
+        #[clap(subcommand)]
+        command: BundleCommand,
+    },
+    /// List or evict the proofs in the proof cache.
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let (output, keys, cache) = (&cli.output, &cli.keys, &cli.proof_cache);
+    match &cli.command {
+        Command::Inspect(args) => commands::inspect::run(args, output),
+        Command::Dump(args) => commands::dump::run(args, output),
+        Command::VerifyNative(args) => commands::verify_native::run(args, output),
+        Command::Execute(args) => commands::execute::run(args, output),
+        Command::Profile(args) => commands::profile::run(args, output),
+        Command::Baseline(args) => commands::baseline::run(args, output),
+        Command::Estimate(args) => commands::estimate::run(args, output),
+        Command::Calibrate(args) => commands::calibrate::run(args, output),
+        Command::Prove(args) => commands::prove::run(args, keys, cache, output),
+        Command::ProveMulti(args) => commands::prove_multi::run(args, keys, output),
+        Command::Batch(args) => commands::batch::run(args, keys, cache, output),
+        Command::Aggregate(args) => commands::aggregate::run(args, keys, output),
+        Command::VerifyProof(args) => commands::verify_proof::run(args, keys, output),
+        Command::Keys { command } => commands::keys::run(command, keys, output),
+        Command::Bundle { command } => commands::bundle::run(command, keys, output),
+        Command::Cache { command } => commands::cache::run(command, cache, output),
+    }
+}
+
+fn main() {
+    sp1_sdk::utils::setup_logger();
+
+    let cli = Cli::parse();
+    if cli.mock {
+        // Every `ProverClient::new()` picks the prover from this, so set it before any is created.
+        std::env::set_var("SP1_PROVER", "mock");
+        tracing::warn!("using the mock prover; proofs will not verify on a real verifier");
+    }
+
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
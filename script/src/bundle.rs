@@ -0,0 +1,215 @@
+//! The `.sxgproof` bundle: one self-describing file per proof.
+//!
+//! A bundle is JSON holding everything needed to check a proof and tell what it proves: the
+//! proof type, SP1 version, program vkey and ELF hash, the raw and decoded public values, the
+//! SHA-256 of the proven input and when and how the bundle was made. It carries the full
+//! `SP1ProofWithPublicValues` when there is one, and the on-chain proof bytes for groth16 and
+//! plonk proofs. Bundles converted from a Solidity fixture only have the latter, and leave the
+//! fields a fixture does not record empty.
+
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::Engine;
+use lib::{encoding::ByteEncoding, sha256_hash, sxg::SXGInput};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1Proof, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::{keys::elf_sha256, DecodedPublicValues, ProofType, Result, SP1SXGProofFixture};
+
+/// The value of [`ProofBundle::format`].
+pub const BUNDLE_FORMAT: &str = "sxgproof";
+/// The bundle layout version this crate reads and writes.
+pub const BUNDLE_VERSION: u32 = 1;
+pub const BUNDLE_EXTENSION: &str = "sxgproof";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofBundle {
+    /// Always [`BUNDLE_FORMAT`], so a bundle is recognizable without its file name.
+    pub format: String,
+    pub version: u32,
+    pub proof_type: ProofType,
+    /// The SP1 version that generated the proof.
+    #[serde(default)]
+    pub sp1_version: Option<String>,
+    /// The program's vkey hash, as pinned by the on-chain verifier.
+    pub vkey: String,
+    #[serde(default)]
+    pub elf_sha256: Option<String>,
+    /// SHA-256 of the proven input's canonical serialization, a current-version input file with
+    /// hex byte fields.
+    #[serde(default)]
+    pub input_sha256: Option<String>,
+    /// The committed public values, `0x`-prefixed hex.
+    pub public_values: String,
+    /// The public values decoded as the SXG program's, when they are.
+    #[serde(default)]
+    pub decoded: Option<DecodedPublicValues>,
+    /// The proof bytes the on-chain verifier takes, `0x`-prefixed hex. Groth16 and plonk only.
+    #[serde(default)]
+    pub proof: Option<String>,
+    /// The bincode-encoded `SP1ProofWithPublicValues`, as base64.
+    #[serde(default)]
+    pub proof_with_io: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+    pub created_by: String,
+}
+
+/// The SHA-256 of `input`'s canonical serialization, as hex.
+pub fn input_sha256(input: &SXGInput) -> Result<String> {
+    Ok(hex::encode(sha256_hash(
+        input.to_json(ByteEncoding::Hex)?.as_bytes(),
+    )))
+}
+
+pub fn proof_type_of(proof: &SP1Proof) -> ProofType {
+    match proof {
+        SP1Proof::Core(_) => ProofType::Core,
+        SP1Proof::Compressed(_) => ProofType::Compressed,
+        SP1Proof::Groth16(_) => ProofType::Groth16,
+        SP1Proof::Plonk(_) => ProofType::Plonk,
+    }
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| format!("invalid hex in `{}`: {}", field, e).into())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn created_by(how: &str) -> String {
+    format!("sxg-script {} ({})", env!("CARGO_PKG_VERSION"), how)
+}
+
+impl ProofBundle {
+    /// Bundles a proof of the program `vk` belongs to, whose ELF is `elf` if known.
+    ///
+    /// The input hash is taken from the proof's stdin, where the SXG program reads its input.
+    pub fn new(
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+        elf: Option<&[u8]>,
+    ) -> Result<Self> {
+        let proof_type = proof_type_of(&proof.proof);
+        let public_values = proof.public_values.as_slice();
+        let input_sha256 = proof
+            .stdin
+            .buffer
+            .first()
+            .and_then(|bytes| bincode::deserialize::<SXGInput>(bytes).ok())
+            .map(|input| input_sha256(&input))
+            .transpose()?;
+
+        Ok(ProofBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            proof_type,
+            sp1_version: Some(proof.sp1_version.clone()),
+            vkey: vk.bytes32(),
+            elf_sha256: elf.map(elf_sha256),
+            input_sha256,
+            public_values: format!("0x{}", hex::encode(public_values)),
+            decoded: DecodedPublicValues::decode(public_values).ok(),
+            proof: proof_type
+                .is_evm()
+                .then(|| format!("0x{}", hex::encode(proof.bytes()))),
+            proof_with_io: Some(
+                base64::prelude::BASE64_STANDARD.encode(bincode::serialize(proof)?),
+            ),
+            created_at: now(),
+            created_by: created_by("prove"),
+        })
+    }
+
+    /// Converts a Solidity fixture, which does not record the proof type, SP1 version, ELF or
+    /// input.
+    pub fn from_fixture(fixture: &SP1SXGProofFixture, proof_type: ProofType) -> Result<Self> {
+        if !proof_type.is_evm() {
+            return Err(format!(
+                "fixtures only hold groth16 and plonk proofs, not {:?}",
+                proof_type
+            )
+            .into());
+        }
+        let public_values = decode_hex("publicValues", &fixture.public_values)?;
+        decode_hex("proof", &fixture.proof)?;
+
+        Ok(ProofBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            proof_type,
+            sp1_version: None,
+            vkey: fixture.vkey.clone(),
+            elf_sha256: None,
+            input_sha256: None,
+            public_values: fixture.public_values.clone(),
+            decoded: DecodedPublicValues::decode(&public_values).ok(),
+            proof: Some(fixture.proof.clone()),
+            proof_with_io: None,
+            created_at: now(),
+            created_by: created_by("converted from a fixture"),
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let bundle: ProofBundle = serde_json::from_str(&json)
+            .map_err(|e| format!("{} is not a proof bundle: {}", path.display(), e))?;
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(format!("{} is not a proof bundle", path.display()).into());
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!(
+                "{} is a version {} bundle; this build reads up to version {}",
+                path.display(),
+                bundle.version,
+                BUNDLE_VERSION
+            )
+            .into());
+        }
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        crate::write_json(path, self)
+    }
+
+    pub fn public_values_bytes(&self) -> Result<Vec<u8>> {
+        decode_hex("public_values", &self.public_values)
+    }
+
+    /// The full proof, if the bundle carries one.
+    pub fn proof_with_public_values(&self) -> Result<Option<SP1ProofWithPublicValues>> {
+        let Some(encoded) = &self.proof_with_io else {
+            return Ok(None);
+        };
+        let bytes = base64::prelude::BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("invalid base64 in `proof_with_io`: {}", e))?;
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    /// The Solidity fixture for the bundle's groth16 or plonk proof.
+    pub fn to_fixture(&self) -> Result<SP1SXGProofFixture> {
+        let Some(proof) = &self.proof else {
+            return Err(format!("{:?} proofs have no fixture", self.proof_type).into());
+        };
+        Ok(SP1SXGProofFixture {
+            result: self.decoded.as_ref().map_or(0, |decoded| decoded.result),
+            vkey: self.vkey.clone(),
+            public_values: self.public_values.clone(),
+            proof: proof.clone(),
+        })
+    }
+}
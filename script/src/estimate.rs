@@ -0,0 +1,180 @@
+//! Predicts how many cycles proving an input will take, before running anything.
+//!
+//! Cycles grow with the bytes the guest hashes, the ECDSA verifications it runs and the bytes it
+//! commits, so a [`CycleModel`] is a cost for each of those. The built-in costs are unmeasured
+//! starting points; `sxg calibrate` fits real ones to executions of the current program.
+
+use std::{fs, path::Path};
+
+use alloy_sol_types::SolType;
+use lib::{
+    sxg::{mi_sha256_record_count, SXGInput, MI_SHA256_RECORD_SIZE},
+    PublicValuesStruct,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Result, SXG_ELF};
+
+/// What the guest spends its cycles on for one input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputFeatures {
+    /// Bytes run through SHA-256: the payload's mi-sha256 records with their chained proofs, and
+    /// the signed message.
+    pub hashed_bytes: u64,
+    pub ecdsa_verifications: u64,
+    pub committed_bytes: u64,
+}
+
+impl InputFeatures {
+    pub fn of(input: &SXGInput) -> Self {
+        let committed = PublicValuesStruct::abi_encode(&input.public_values(true));
+        // Every record but the last is hashed with the next one's 32-byte proof and a flag byte,
+        // the last with only the flag.
+        let records = mi_sha256_record_count(input.payload.len(), MI_SHA256_RECORD_SIZE);
+        let chaining = records.saturating_sub(1) * 33 + records.min(1);
+        InputFeatures {
+            hashed_bytes: (input.payload.len() + chaining + input.final_payload.len()) as u64,
+            ecdsa_verifications: 1,
+            committed_bytes: committed.len() as u64,
+        }
+    }
+
+    fn as_array(&self) -> [f64; 3] {
+        [
+            self.hashed_bytes as f64,
+            self.ecdsa_verifications as f64,
+            self.committed_bytes as f64,
+        ]
+    }
+}
+
+impl std::ops::Add for InputFeatures {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        InputFeatures {
+            hashed_bytes: self.hashed_bytes + other.hashed_bytes,
+            ecdsa_verifications: self.ecdsa_verifications + other.ecdsa_verifications,
+            committed_bytes: self.committed_bytes + other.committed_bytes,
+        }
+    }
+}
+
+/// Cycle costs per unit of each [`InputFeatures`] field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleModel {
+    pub per_hashed_byte: f64,
+    /// Every input is verified exactly once, so its fixed overhead (reading stdin, parsing the
+    /// signed message) is folded into this cost.
+    pub per_ecdsa_verification: f64,
+    pub per_committed_byte: f64,
+    /// SHA-256 of the ELF the model was fitted against, if it was fitted at all.
+    #[serde(default)]
+    pub elf_sha256: Option<String>,
+    /// How many executions the model was fitted to.
+    #[serde(default)]
+    pub samples: usize,
+}
+
+impl Default for CycleModel {
+    fn default() -> Self {
+        CycleModel {
+            per_hashed_byte: 8.0,
+            per_ecdsa_verification: 1_800_000.0,
+            per_committed_byte: 60.0,
+            elf_sha256: None,
+            samples: 0,
+        }
+    }
+}
+
+/// One calibration data point: an input's features and the cycles its execution took.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub features: InputFeatures,
+    pub cycles: u64,
+}
+
+impl CycleModel {
+    /// Reads a model written by `sxg calibrate`, warning if it was fitted to another program
+    /// build.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let model: CycleModel = serde_json::from_str(&json)
+            .map_err(|e| format!("invalid cycle model in {}: {}", path.display(), e))?;
+        if let Some(elf_sha256) = &model.elf_sha256 {
+            if *elf_sha256 != hex::encode(lib::sha256_hash(SXG_ELF)) {
+                tracing::warn!(
+                    "{} was calibrated against another build of the program; rerun `sxg calibrate`",
+                    path.display()
+                );
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn estimate(&self, features: &InputFeatures) -> u64 {
+        let [hashed, verifications, bytes] = features.as_array();
+        let cycles = self.per_hashed_byte * hashed
+            + self.per_ecdsa_verification * verifications
+            + self.per_committed_byte * bytes;
+        cycles.max(0.0).round() as u64
+    }
+
+    /// Fits the costs to `samples` by least squares.
+    ///
+    /// Every input runs one verification, so its cost is the fitted constant. The samples need at
+    /// least three distinct combinations of hashed and committed size, or the costs cannot be told
+    /// apart.
+    pub fn fit(samples: &[Sample]) -> Result<Self> {
+        // Normal equations `(XᵀX) c = Xᵀy`, solved by Gaussian elimination.
+        let mut a = [[0.0f64; 4]; 3];
+        for sample in samples {
+            let x = sample.features.as_array();
+            for i in 0..3 {
+                for j in 0..3 {
+                    a[i][j] += x[i] * x[j];
+                }
+                a[i][3] += x[i] * sample.cycles as f64;
+            }
+        }
+
+        let norm = a
+            .iter()
+            .flat_map(|row| &row[..3])
+            .fold(0.0f64, |m, v| m.max(v.abs()));
+        for col in 0..3 {
+            let pivot = (col..3)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap_or(col);
+            a.swap(col, pivot);
+            let scale = a[col][col];
+            if scale.abs() <= 1e-9 * norm {
+                return Err(format!(
+                    "cannot fit a cycle model to {} samples; calibrate with inputs of different \
+                     payload and disclosure sizes",
+                    samples.len()
+                )
+                .into());
+            }
+            let pivot_row = a[col];
+            for (i, row) in a.iter_mut().enumerate() {
+                if i != col {
+                    let factor = row[col] / scale;
+                    for (cell, p) in row.iter_mut().zip(pivot_row).skip(col) {
+                        *cell -= factor * p;
+                    }
+                }
+            }
+        }
+
+        Ok(CycleModel {
+            per_hashed_byte: a[0][3] / a[0][0],
+            per_ecdsa_verification: a[1][3] / a[1][1],
+            per_committed_byte: a[2][3] / a[2][2],
+            elf_sha256: Some(hex::encode(lib::sha256_hash(SXG_ELF))),
+            samples: samples.len(),
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! An on-disk cache of the SXG program's proving and verifying keys.
+//!
+//! `client.setup` is slow and deterministic for a given ELF, so its output is stored under a
+//! directory named after the ELF's SHA-256 and reused until the program changes. Each program
+//! (see [`crate::SXG_ELF`] and its siblings) gets its own directory.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+use lib::sha256_hash;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProvingKey, SP1VerifyingKey};
+
+use crate::{write_json, Result, SXG_ELF};
+
+pub const PK_FILE: &str = "pk.bin";
+pub const VK_FILE: &str = "vk.bin";
+/// The vkey hash as JSON, for tooling that cannot decode `vk.bin`.
+pub const VKEY_FILE: &str = "vkey.json";
+
+/// The contents of [`VKEY_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VkeyInfo {
+    pub elf_sha256: String,
+    pub vkey: String,
+}
+
+/// Where proving and verifying keys are cached.
+#[derive(Args, Debug)]
+pub struct KeyCache {
+    /// Directory that proving and verifying keys are cached in, one subdirectory per ELF hash.
+    #[clap(long, global = true, env = "SXG_KEY_DIR", default_value = ".sxg-keys")]
+    pub key_dir: PathBuf,
+}
+
+/// The hex SHA-256 of `elf`, which names its cache directory.
+pub fn elf_sha256(elf: &[u8]) -> String {
+    hex::encode(sha256_hash(elf))
+}
+
+fn read_bincode<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?;
+    match bincode::deserialize(&bytes) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("ignoring unreadable cached key {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn write_bincode<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    // Written next to the target and renamed, so a concurrent reader never sees half a key.
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bincode::serialize(value)?)
+        .map_err(|e| format!("failed to write {}: {}", partial.display(), e))?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Reads a bincode-encoded verifying key, such as the `vk.bin` written by `sxg keys export`.
+pub fn read_verifying_key(path: &Path) -> Result<SP1VerifyingKey> {
+    let bytes = fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    bincode::deserialize(&bytes)
+        .map_err(|e| format!("invalid verifying key in {}: {}", path.display(), e).into())
+}
+
+/// A `--vkey` argument: either the `0x`-prefixed vkey hash that fixtures and `sxg keys show-vkey`
+/// print, or the path to a bincode-encoded verifying key.
+///
+/// Verifying off-chain needs the whole key, which the hash does not contain, so a hash only
+/// selects the embedded program's key after checking that it is that key's hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VkeyArg {
+    Hash(String),
+    Path(PathBuf),
+}
+
+impl std::str::FromStr for VkeyArg {
+    type Err = std::convert::Infallible;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let is_hash = value
+            .strip_prefix("0x")
+            .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()));
+        Ok(if is_hash {
+            VkeyArg::Hash(value.to_ascii_lowercase())
+        } else {
+            VkeyArg::Path(value.into())
+        })
+    }
+}
+
+impl KeyCache {
+    /// Resolves `vkey` to a verifying key, defaulting to the SXG program's cached one.
+    pub fn resolve_verifying_key(&self, vkey: Option<&VkeyArg>) -> Result<SP1VerifyingKey> {
+        match vkey {
+            None => self.verifying_key(),
+            Some(VkeyArg::Path(path)) => read_verifying_key(path),
+            Some(VkeyArg::Hash(hash)) => {
+                let vk = self.verifying_key()?;
+                if vk.bytes32() != *hash {
+                    return Err(format!(
+                        "{} is not the vkey of the embedded program ({}); pass the program's \
+                         vk.bin instead",
+                        hash,
+                        vk.bytes32()
+                    )
+                    .into());
+                }
+                Ok(vk)
+            }
+        }
+    }
+
+    /// The cache directory for the SXG program.
+    pub fn dir(&self) -> PathBuf {
+        self.dir_for(SXG_ELF)
+    }
+
+    pub fn dir_for(&self, elf: &[u8]) -> PathBuf {
+        self.key_dir.join(elf_sha256(elf))
+    }
+
+    /// Returns the SXG program's keys, see [`KeyCache::keys_for`].
+    pub fn keys(&self, client: &ProverClient) -> Result<(SP1ProvingKey, SP1VerifyingKey)> {
+        self.keys_for(client, SXG_ELF)
+    }
+
+    /// Returns the cached keys for `elf`, running `client.setup` and caching its output on a miss.
+    pub fn keys_for(
+        &self,
+        client: &ProverClient,
+        elf: &[u8],
+    ) -> Result<(SP1ProvingKey, SP1VerifyingKey)> {
+        let dir = self.dir_for(elf);
+        let cached = read_bincode(&dir.join(PK_FILE)).zip(read_bincode(&dir.join(VK_FILE)));
+        if let Some(keys) = cached {
+            return Ok(keys);
+        }
+
+        tracing::info!("no cached keys for this ELF, running setup");
+        let (pk, vk) = client.setup(elf);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        write_bincode(&dir.join(PK_FILE), &pk)?;
+        write_bincode(&dir.join(VK_FILE), &vk)?;
+        write_json(
+            &dir.join(VKEY_FILE),
+            &VkeyInfo {
+                elf_sha256: elf_sha256(elf),
+                vkey: vk.bytes32(),
+            },
+        )?;
+        Ok((pk, vk))
+    }
+
+    /// Returns the SXG program's cached verifying key, only creating a prover when it has to run
+    /// setup.
+    pub fn verifying_key(&self) -> Result<SP1VerifyingKey> {
+        match read_bincode(&self.dir().join(VK_FILE)) {
+            Some(vk) => Ok(vk),
+            None => Ok(self.keys(&ProverClient::new())?.1),
+        }
+    }
+}
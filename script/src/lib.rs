@@ -0,0 +1,184 @@
+//! The program ELFs, proof types and Solidity fixture format shared by the `sxg` binary.
+//!
+//! Services that embed proving should start from [`prover::SxgProver`], and tests that need proofs
+//! without paying for real proving from [`prover::SxgProver::mock`].
+
+#[cfg(feature = "async")]
+pub mod async_prover;
+pub mod baseline;
+pub mod bundle;
+pub mod estimate;
+pub mod keys;
+pub mod proof_cache;
+pub mod prover;
+
+use std::{fs, path::Path};
+
+use alloy_sol_types::SolType;
+use clap::ValueEnum;
+use lib::{sxg::SXGInput, vkey_hash_bytes, LowSPolicy, PublicValuesStruct};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{
+    HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const SXG_ELF: &[u8] = include_bytes!("../../elf/riscv32im-succinct-zkvm-elf");
+
+/// The ELF of the batch program, which verifies several inputs in one execution.
+pub const SXG_BATCH_ELF: &[u8] = include_bytes!("../../elf/sxg-batch-elf");
+
+/// The ELF of the aggregation program, which verifies existing SXG proofs in one proof.
+pub const SXG_AGGREGATION_ELF: &[u8] = include_bytes!("../../elf/sxg-aggregation-elf");
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The proof types the SP1 prover can produce.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofType {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+impl ProofType {
+    /// Whether the proof can be verified on-chain, and so gets a Solidity fixture.
+    pub fn is_evm(&self) -> bool {
+        matches!(self, ProofType::Groth16 | ProofType::Plonk)
+    }
+}
+
+/// The committed [`PublicValuesStruct`], decoded for display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedPublicValues {
+    pub result: u32,
+    pub data_to_verify: String,
+    pub data_to_verify_hex: String,
+    pub signature_hash: String,
+    pub public_key: String,
+    pub url: String,
+    /// `None` when the committed value names no known policy.
+    #[serde(default)]
+    pub low_s_policy: Option<LowSPolicy>,
+}
+
+impl From<PublicValuesStruct> for DecodedPublicValues {
+    fn from(values: PublicValuesStruct) -> Self {
+        let PublicValuesStruct {
+            result,
+            data_to_verify,
+            signature_hash,
+            public_key,
+            url,
+            low_s_policy,
+        } = values;
+
+        DecodedPublicValues {
+            result,
+            data_to_verify: String::from_utf8_lossy(&data_to_verify).into_owned(),
+            data_to_verify_hex: format!("0x{}", hex::encode(&data_to_verify)),
+            signature_hash: signature_hash.to_string(),
+            public_key: public_key.to_string(),
+            url,
+            low_s_policy: LowSPolicy::from_committed(low_s_policy),
+        }
+    }
+}
+
+impl DecodedPublicValues {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(PublicValuesStruct::abi_decode(bytes, true)?.into())
+    }
+
+    pub fn print(&self) {
+        println!("SXG verification result: {}", self.result);
+        println!("URL: {}", self.url);
+        println!("Data Verified: {:?}", self.data_to_verify);
+        println!("Signature Hash: {}", self.signature_hash);
+        println!("Public Key: {}", self.public_key);
+        if let Some(policy) = self.low_s_policy {
+            println!("Low-S Policy: {:?}", policy);
+        }
+    }
+}
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SP1SXGProofFixture {
+    pub result: u32,
+    pub vkey: String,
+    pub public_values: String,
+    pub proof: String,
+}
+
+impl SP1SXGProofFixture {
+    /// The fixture for a groth16 or plonk `proof` of the program `vk` belongs to.
+    pub fn new(result: u32, vk: &SP1VerifyingKey, proof: &SP1ProofWithPublicValues) -> Self {
+        SP1SXGProofFixture {
+            result,
+            vkey: vk.bytes32(),
+            public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            proof: format!("0x{}", hex::encode(proof.bytes())),
+        }
+    }
+}
+
+/// The fixture for a groth16 or plonk proof of the aggregation program. Unlike
+/// [`SP1SXGProofFixture`] it has no `result`: the aggregation program commits each aggregated
+/// proof's public-values digest, and their results are only in the public values they hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SP1AggregationProofFixture {
+    /// The aggregation program's vkey.
+    pub vkey: String,
+    /// The vkey of the SXG program whose proofs were aggregated, in the form the aggregation
+    /// program commits as `sxg_vkey_hash`: the big-endian bytes of `vk.hash_u32()`. This is not
+    /// the BN254 `vkey` that SXG fixtures and verifier contracts use.
+    pub sxg_vkey: String,
+    pub public_values: String,
+    pub proof: String,
+}
+
+impl SP1AggregationProofFixture {
+    pub fn new(
+        vk: &SP1VerifyingKey,
+        sxg_vk: &SP1VerifyingKey,
+        proof: &SP1ProofWithPublicValues,
+    ) -> Self {
+        SP1AggregationProofFixture {
+            vkey: vk.bytes32(),
+            sxg_vkey: format!("0x{}", hex::encode(vkey_hash_bytes(&sxg_vk.hash_u32()))),
+            public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            proof: format!("0x{}", hex::encode(proof.bytes())),
+        }
+    }
+}
+
+/// Proves `input` against the SXG program's `pk`, without verifying the proof.
+pub fn prove(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    input: &SXGInput,
+    proof_type: ProofType,
+) -> Result<SP1ProofWithPublicValues> {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(input);
+
+    let builder = client.prove(pk, stdin);
+    let proof = match proof_type {
+        ProofType::Core => builder.core().run(),
+        ProofType::Compressed => builder.compressed().run(),
+        ProofType::Groth16 => builder.groth16().run(),
+        ProofType::Plonk => builder.plonk().run(),
+    }?;
+    Ok(proof)
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?)
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(())
+}
@@ -0,0 +1,263 @@
+//! A content-addressed cache of proofs, so the same input is only proven once per program build.
+//!
+//! Entries are keyed by the SHA-256 of the input's canonical serialization (a current-version
+//! input file with hex byte fields), the ELF hash and the proof type, so a cached proof is only
+//! ever returned for exactly the statement it proves. The least recently used entries are evicted
+//! once the cache grows past its size limit. Mock proofs are never cached.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use clap::Args;
+use lib::{encoding::ByteEncoding, sha256_hash, sxg::SXGInput};
+use serde::Serialize;
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1VerifyingKey};
+
+use crate::{keys::elf_sha256, ProofType, Result, SXG_ELF};
+
+const ENTRY_EXTENSION: &str = "proof";
+
+/// Numbers this process's partial writes, so concurrent `put`s of one key never share a file.
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Where proofs are cached, and how much space they may take.
+#[derive(Args, Debug)]
+pub struct ProofCache {
+    /// Directory that generated proofs are cached in.
+    #[clap(
+        long,
+        global = true,
+        env = "SXG_PROOF_CACHE_DIR",
+        default_value = ".sxg-proofs"
+    )]
+    pub proof_cache_dir: PathBuf,
+
+    /// Most bytes the proof cache may hold before the least recently used proofs are evicted.
+    #[clap(
+        long,
+        global = true,
+        env = "SXG_PROOF_CACHE_MAX_BYTES",
+        default_value_t = 10 * 1024 * 1024 * 1024
+    )]
+    pub proof_cache_max_bytes: u64,
+
+    /// Always prove, neither reading nor writing the proof cache.
+    #[clap(long, global = true)]
+    pub no_proof_cache: bool,
+}
+
+/// A cached proof on disk.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Seconds since the entry was last written or returned.
+    pub idle_secs: u64,
+}
+
+/// What an eviction removed and left behind.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Eviction {
+    pub removed: Vec<CacheEntry>,
+    pub freed_bytes: u64,
+    pub remaining_entries: usize,
+    pub remaining_bytes: u64,
+}
+
+/// Whether `SP1_PROVER` selects SP1's mock prover.
+pub(crate) fn is_mock() -> bool {
+    std::env::var("SP1_PROVER").is_ok_and(|prover| prover.eq_ignore_ascii_case("mock"))
+}
+
+impl ProofCache {
+    /// The cache key for proving `input` against `elf` as a `proof_type` proof.
+    pub fn key(input: &SXGInput, elf: &[u8], proof_type: ProofType) -> Result<String> {
+        let canonical = input.to_json(ByteEncoding::Hex)?;
+        let material = format!("{}\n{:?}\n{}", elf_sha256(elf), proof_type, canonical);
+        Ok(hex::encode(sha256_hash(material.as_bytes())))
+    }
+
+    fn enabled(&self) -> bool {
+        !self.no_proof_cache && !is_mock()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.proof_cache_dir
+            .join(key)
+            .with_extension(ENTRY_EXTENSION)
+    }
+
+    /// Returns the cached proof for `key`, marking it as recently used.
+    pub fn get(&self, key: &str) -> Option<SP1ProofWithPublicValues> {
+        if !self.enabled() {
+            return None;
+        }
+        let path = self.path(key);
+        if !path.exists() {
+            return None;
+        }
+        match SP1ProofWithPublicValues::load(&path) {
+            Ok(proof) => {
+                let touched = fs::File::options()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(SystemTime::now()));
+                if let Err(e) = touched {
+                    tracing::debug!("failed to touch {}: {}", path.display(), e);
+                }
+                tracing::info!("using the cached proof {}", path.display());
+                Some(proof)
+            }
+            Err(e) => {
+                tracing::warn!("ignoring unreadable cached proof {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Caches `proof` under `key`, then evicts down to the size limit.
+    pub fn put(&self, key: &str, proof: &SP1ProofWithPublicValues) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.proof_cache_dir)
+            .map_err(|e| format!("failed to create {}: {}", self.proof_cache_dir.display(), e))?;
+
+        // Saved next to the entry under a name no other writer uses, then renamed, so a concurrent
+        // reader never sees half a proof and concurrent writers never interleave.
+        let path = self.path(key);
+        let partial = self.proof_cache_dir.join(format!(
+            "{}.{}-{}.partial",
+            key,
+            process::id(),
+            PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let saved = proof
+            .save(&partial)
+            .map_err(Into::into)
+            .and_then(|()| fs::rename(&partial, &path).map_err(Into::into));
+        if let Err(e) = saved {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+
+        self.evict(self.proof_cache_max_bytes, None)?;
+        Ok(())
+    }
+
+    /// Caches `proof` under `key`, logging a failure instead of returning it: the proof is valid
+    /// whether or not it could be cached.
+    pub(crate) fn put_or_warn(&self, key: &str, proof: &SP1ProofWithPublicValues) {
+        if let Err(e) = self.put(key, proof) {
+            tracing::warn!("failed to cache the proof {}: {}", key, e);
+        }
+    }
+
+    /// Returns a verified proof of `input` against the SXG program, from the cache if it holds
+    /// one and by proving it otherwise. The flag says whether it came from the cache.
+    pub fn prove(
+        &self,
+        client: &ProverClient,
+        (pk, vk): (&SP1ProvingKey, &SP1VerifyingKey),
+        input: &SXGInput,
+        proof_type: ProofType,
+    ) -> Result<(SP1ProofWithPublicValues, bool)> {
+        let key = Self::key(input, SXG_ELF, proof_type)?;
+        if let Some(proof) = self.get(&key) {
+            match client.verify(&proof, vk) {
+                Ok(()) => return Ok((proof, true)),
+                Err(e) => {
+                    tracing::warn!("ignoring cached proof {} that does not verify: {}", key, e)
+                }
+            }
+        }
+
+        let proof = crate::prove(client, pk, input, proof_type)?;
+        client.verify(&proof, vk)?;
+        self.put_or_warn(&key, &proof);
+        Ok((proof, false))
+    }
+
+    /// Every cached proof, least recently used first.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let dir = match fs::read_dir(&self.proof_cache_dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(
+                    format!("failed to read {}: {}", self.proof_cache_dir.display(), e).into(),
+                )
+            }
+        };
+
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        for entry in dir {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(ENTRY_EXTENSION) {
+                continue;
+            }
+            // Another process may evict the entry between listing and reading it.
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let idle = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            entries.push(CacheEntry {
+                key: key_of(&path),
+                path,
+                bytes: metadata.len(),
+                idle_secs: idle.as_secs(),
+            });
+        }
+        entries.sort_by(|a, b| b.idle_secs.cmp(&a.idle_secs).then(a.key.cmp(&b.key)));
+        Ok(entries)
+    }
+
+    /// Removes entries idle for longer than `max_idle`, then the least recently used ones until
+    /// the rest fit in `max_bytes`.
+    pub fn evict(&self, max_bytes: u64, max_idle: Option<Duration>) -> Result<Eviction> {
+        let entries = self.entries()?;
+        let mut remaining_bytes: u64 = entries.iter().map(|entry| entry.bytes).sum();
+        let mut eviction = Eviction::default();
+
+        for entry in entries {
+            let idle = max_idle.is_some_and(|max| entry.idle_secs > max.as_secs());
+            if remaining_bytes <= max_bytes && !idle {
+                eviction.remaining_entries += 1;
+                continue;
+            }
+            remaining_bytes -= entry.bytes;
+            match fs::remove_file(&entry.path) {
+                Ok(()) => {
+                    eviction.freed_bytes += entry.bytes;
+                    eviction.removed.push(entry);
+                }
+                // Already evicted by a concurrent writer.
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(format!("failed to remove {}: {}", entry.path.display(), e).into())
+                }
+            }
+        }
+        eviction.remaining_bytes = remaining_bytes;
+        Ok(eviction)
+    }
+}
+
+fn key_of(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,307 @@
+//! [`SxgProver`], the entry point for proving SXG inputs from Rust without going through the `sxg`
+//! binary.
+//!
+//! ```no_run
+//! # fn main() -> sxg_script::Result<()> {
+//! use sxg_script::prover::{ProofKind, SxgProver};
+//!
+//! let json = std::fs::read_to_string("sxg_input_1.json")?;
+//! let input = lib::schema::load_input(&json)?;
+//!
+//! let prover = SxgProver::new();
+//! let execution = prover.execute(&input)?;
+//! println!("{} cycles", execution.cycles);
+//!
+//! let proof = prover.prove(&input, ProofKind::Groth16)?;
+//! let verification = prover.verify(&proof.bundle)?;
+//! assert_eq!(verification.public_values.result, 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use lib::sxg::SXGInput;
+use serde::Serialize;
+use sp1_sdk::{
+    HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+
+use crate::{
+    bundle::{proof_type_of, ProofBundle},
+    keys::KeyCache,
+    proof_cache::{is_mock, ProofCache},
+    DecodedPublicValues, ProofType, Result, SP1SXGProofFixture, SXG_ELF,
+};
+
+/// The kind of proof [`SxgProver::prove`] generates.
+pub type ProofKind = ProofType;
+
+/// What executing the SXG program on an input committed, and what it cost.
+#[derive(Debug, Clone, Serialize)]
+pub struct Execution {
+    pub public_values: DecodedPublicValues,
+    pub cycles: u64,
+    /// Cycles by the stages the guest brackets with `lib::track_cycles!`.
+    pub stage_cycles: BTreeMap<String, u64>,
+}
+
+/// A verified proof of an input.
+#[derive(Clone)]
+pub struct SxgProof {
+    pub proof: SP1ProofWithPublicValues,
+    /// The proof as a `.sxgproof` bundle, ready to [`ProofBundle::write`].
+    pub bundle: ProofBundle,
+    pub public_values: DecodedPublicValues,
+    /// Whether the proof came from the proof cache instead of being generated.
+    pub cached: bool,
+}
+
+impl SxgProof {
+    /// The Solidity fixture for a groth16 or plonk proof.
+    pub fn fixture(&self) -> Result<SP1SXGProofFixture> {
+        self.bundle.to_fixture()
+    }
+}
+
+/// What a bundle's proof was checked to prove.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub proof_type: ProofType,
+    pub vkey: String,
+    pub public_values: DecodedPublicValues,
+}
+
+/// A stage of [`SxgProver::prove_with_progress`], reported as it starts.
+///
+/// `Proving` covers the shard proofs and, for every kind but core, their recursive compression.
+/// SP1 makes a groth16 or plonk proof in the same call, so those kinds report `Wrapping` right
+/// after `Proving`, as that call starts, and it lasts until the wrapped proof is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum Progress {
+    /// Running setup for the program. Only reported by the `async` feature's `AsyncSxgProver`,
+    /// which runs setup on the first job instead of up front.
+    Setup,
+    /// Executing the program, to find its cycle count before proving.
+    Executing,
+    Proving {
+        kind: ProofKind,
+        cycles: u64,
+        /// The cycle count divided by SP1's shard size.
+        shards: u64,
+    },
+    /// Proving, compressing and wrapping the proof in a groth16 or plonk proof for on-chain
+    /// verification, all in SP1's one call.
+    Wrapping { kind: ProofKind },
+    /// Verifying a new proof, or one from the proof cache before it is reused.
+    Verifying,
+}
+
+/// SP1's default shard size in cycles, unless overridden by `SHARD_SIZE`.
+const DEFAULT_SHARD_SIZE: u64 = 1 << 22;
+
+fn shard_count(cycles: u64) -> u64 {
+    let shard_size = std::env::var("SHARD_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .filter(|&size| size > 0)
+        .unwrap_or(DEFAULT_SHARD_SIZE);
+    cycles.div_ceil(shard_size).max(1)
+}
+
+/// Executes, proves and verifies inputs of the SXG program.
+///
+/// Setup runs once, when the prover is created, so one instance should serve many proofs. It is
+/// `Sync`, so threads can share one.
+pub struct SxgProver {
+    client: ProverClient,
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+    proof_cache: Option<ProofCache>,
+    mock: bool,
+}
+
+impl Default for SxgProver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SxgProver {
+    /// A prover chosen by `SP1_PROVER`, as in [`ProverClient::new`], with keys from running setup.
+    pub fn new() -> Self {
+        let client = ProverClient::new();
+        let (pk, vk) = client.setup(SXG_ELF);
+        Self::with_keys(client, pk, vk)
+    }
+
+    /// Like [`SxgProver::new`], but takes the keys from `keys`, running setup only on a miss.
+    pub fn with_key_cache(keys: &KeyCache) -> Result<Self> {
+        let client = ProverClient::new();
+        let (pk, vk) = keys.keys(&client)?;
+        Ok(Self::with_keys(client, pk, vk))
+    }
+
+    /// A prover using SP1's mock prover, for fast end-to-end tests.
+    ///
+    /// The mock prover executes the program for real, so the public values are exactly what a
+    /// real proof would commit, but it skips proving. Mock groth16 and plonk proofs have empty
+    /// proof bytes, which only `SP1MockVerifier` (or a mocked verifier call in Foundry) accepts;
+    /// [`SxgProof::fixture`] turns them into Solidity fixtures.
+    pub fn mock() -> Self {
+        let client = ProverClient::mock();
+        let (pk, vk) = client.setup(SXG_ELF);
+        SxgProver {
+            mock: true,
+            ..Self::with_keys(client, pk, vk)
+        }
+    }
+
+    fn with_keys(client: ProverClient, pk: SP1ProvingKey, vk: SP1VerifyingKey) -> Self {
+        SxgProver {
+            client,
+            pk,
+            vk,
+            proof_cache: None,
+            mock: is_mock(),
+        }
+    }
+
+    /// Reuses proofs from `cache`, and caches new ones in it. A mock prover ignores the cache.
+    pub fn with_proof_cache(mut self, cache: ProofCache) -> Self {
+        self.proof_cache = Some(cache);
+        self
+    }
+
+    pub fn verifying_key(&self) -> &SP1VerifyingKey {
+        &self.vk
+    }
+
+    /// The vkey hash the on-chain verifier pins the program to.
+    pub fn vkey(&self) -> String {
+        self.vk.bytes32()
+    }
+
+    /// Runs the program on `input` without proving.
+    pub fn execute(&self, input: &SXGInput) -> Result<Execution> {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(input);
+        let (public_values, report) = self.client.execute(SXG_ELF, stdin).run()?;
+
+        Ok(Execution {
+            public_values: DecodedPublicValues::decode(public_values.as_slice())?,
+            cycles: report.total_instruction_count(),
+            stage_cycles: report.cycle_tracker.into_iter().collect(),
+        })
+    }
+
+    /// Proves `input` as a `kind` proof, or takes its proof from the proof cache, and verifies it.
+    pub fn prove(&self, input: &SXGInput, kind: ProofKind) -> Result<SxgProof> {
+        self.prove_cached(input, kind, &mut |_| Ok(()), |_| {
+            crate::prove(&self.client, &self.pk, input, kind)
+        })
+    }
+
+    /// Like [`SxgProver::prove`], calling `on_progress` as each stage starts. If it returns an
+    /// error, proving stops there and returns that error.
+    ///
+    /// Reporting the cycle and shard counts means executing the program before proving it, so
+    /// this is slower than [`SxgProver::prove`] by one execution.
+    pub fn prove_with_progress(
+        &self,
+        input: &SXGInput,
+        kind: ProofKind,
+        mut on_progress: impl FnMut(Progress) -> Result<()>,
+    ) -> Result<SxgProof> {
+        self.prove_cached(input, kind, &mut on_progress, |on_progress| {
+            on_progress(Progress::Executing)?;
+            let cycles = self.execute(input)?.cycles;
+            on_progress(Progress::Proving {
+                kind,
+                cycles,
+                shards: shard_count(cycles),
+            })?;
+            if matches!(kind, ProofKind::Groth16 | ProofKind::Plonk) {
+                on_progress(Progress::Wrapping { kind })?;
+            }
+            crate::prove(&self.client, &self.pk, input, kind)
+        })
+    }
+
+    /// Takes `input`'s proof from the proof cache, or makes it with `prove` and caches it. Both
+    /// report their stages to `on_progress`.
+    fn prove_cached(
+        &self,
+        input: &SXGInput,
+        kind: ProofKind,
+        on_progress: &mut dyn FnMut(Progress) -> Result<()>,
+        prove: impl FnOnce(&mut dyn FnMut(Progress) -> Result<()>) -> Result<SP1ProofWithPublicValues>,
+    ) -> Result<SxgProof> {
+        let cache = self.proof_cache.as_ref().filter(|_| !self.mock);
+        let key = ProofCache::key(input, SXG_ELF, kind)?;
+        if let Some(proof) = cache.and_then(|cache| cache.get(&key)) {
+            on_progress(Progress::Verifying)?;
+            match self.client.verify(&proof, &self.vk) {
+                Ok(()) => return self.finish(proof, true),
+                Err(e) => {
+                    tracing::warn!("ignoring cached proof {} that does not verify: {}", key, e)
+                }
+            }
+        }
+
+        let proof = prove(on_progress)?;
+        on_progress(Progress::Verifying)?;
+        self.client.verify(&proof, &self.vk)?;
+        if let Some(cache) = cache {
+            cache.put_or_warn(&key, &proof);
+        }
+        self.finish(proof, false)
+    }
+
+    fn finish(&self, proof: SP1ProofWithPublicValues, cached: bool) -> Result<SxgProof> {
+        Ok(SxgProof {
+            public_values: DecodedPublicValues::decode(proof.public_values.as_slice())?,
+            bundle: ProofBundle::new(&proof, &self.vk, Some(SXG_ELF))?,
+            proof,
+            cached,
+        })
+    }
+
+    /// Checks that `bundle` holds a valid proof of this program whose public values are the ones
+    /// it claims.
+    ///
+    /// Bundles converted from a Solidity fixture carry no SP1 proof, and can only be verified
+    /// on-chain.
+    pub fn verify(&self, bundle: &ProofBundle) -> Result<Verification> {
+        let vkey = self.vkey();
+        if bundle.vkey != vkey {
+            return Err(format!(
+                "the bundle proves the program with vkey {}, not this one ({})",
+                bundle.vkey, vkey
+            )
+            .into());
+        }
+        let Some(proof) = bundle.proof_with_public_values()? else {
+            return Err("the bundle carries no SP1 proof; verify its fixture on-chain".into());
+        };
+        if proof.public_values.as_slice() != bundle.public_values_bytes()?.as_slice() {
+            return Err("the bundle's public values do not match its proof".into());
+        }
+        let proof_type = proof_type_of(&proof.proof);
+        if proof_type != bundle.proof_type {
+            return Err(format!(
+                "the bundle claims a {:?} proof but holds a {:?} one",
+                bundle.proof_type, proof_type
+            )
+            .into());
+        }
+        self.client.verify(&proof, &self.vk)?;
+
+        Ok(Verification {
+            proof_type,
+            vkey,
+            public_values: DecodedPublicValues::decode(proof.public_values.as_slice())?,
+        })
+    }
+}
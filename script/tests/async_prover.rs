@@ -0,0 +1,55 @@
+//! Drives [`AsyncSxgProver`] with the mock prover: progress is reported in order, a cancelled job
+//! fails with [`Cancelled`] and one that cannot be cancelled never does.
+
+#![cfg(feature = "async")]
+
+use sxg_script::{
+    async_prover::{AsyncSxgProver, Cancelled},
+    baseline::{self, BUNDLED_INPUTS},
+    prover::{Progress, ProofKind},
+};
+
+#[tokio::test]
+async fn mock_proof_reports_progress() {
+    let prover = AsyncSxgProver::mock(1);
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+
+    let mut job = prover.prove(input, ProofKind::Compressed);
+    let mut stages = Vec::new();
+    while let Some(progress) = job.next_progress().await {
+        stages.push(progress);
+    }
+    let proof = job.wait().await.unwrap();
+    assert_eq!(proof.public_values.result, 1);
+
+    assert_eq!(stages[..2], [Progress::Setup, Progress::Executing]);
+    assert!(matches!(
+        stages[2],
+        Progress::Proving { kind: ProofKind::Compressed, cycles, shards } if cycles > 0 && shards > 0
+    ));
+    assert_eq!(stages[3..], [Progress::Verifying]);
+}
+
+#[tokio::test]
+async fn cancelled_job_fails() {
+    let prover = AsyncSxgProver::mock(1);
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+
+    let job = prover.prove(input, ProofKind::Core);
+    job.cancel();
+    let error = job.wait().await.err().unwrap();
+    assert!(error.is::<Cancelled>(), "{}", error);
+}
+
+#[tokio::test]
+async fn execute_is_never_cancelled() {
+    // Setup fails at once, so each call gets as far as a slot without executing anything.
+    let prover = AsyncSxgProver::with_setup(1, || Err("no prover in this test".into()));
+
+    for _ in 0..100 {
+        let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+        let error = prover.execute(input).await.err().unwrap();
+        assert!(!error.is::<Cancelled>(), "execute was cancelled");
+        assert_eq!(error.to_string(), "no prover in this test");
+    }
+}
@@ -0,0 +1,61 @@
+//! Executes the guest on the bundled inputs and fails if any got slower than
+//! `cycle-baseline.json` allows, or is missing from it. Run `sxg baseline update`, or this test
+//! with `SXG_UPDATE_BASELINE=1`, after an intentional change.
+
+use sp1_sdk::ProverClient;
+use sxg_script::baseline::{self, Comparison, CycleBaseline, BUNDLED_INPUTS, UPDATE_ENV};
+
+#[test]
+fn cycle_counts_within_baseline() {
+    let path = baseline::default_path();
+    let mut baseline = CycleBaseline::load(&path).unwrap();
+    let client = ProverClient::new();
+
+    let mut failures = Vec::new();
+    let mut measured = Vec::new();
+    for name in BUNDLED_INPUTS {
+        let input = baseline::load_bundled(name).unwrap();
+        let cycles = baseline::execute_cycles(&client, &input).unwrap();
+        measured.push((name.to_string(), cycles));
+        match baseline.compare(name, cycles) {
+            Comparison::Missing => {
+                failures.push(format!("{}: {} cycles, not in the baseline", name, cycles));
+            }
+            Comparison::Faster {
+                baseline,
+                change_percent,
+            } => {
+                eprintln!(
+                    "{}: {} cycles, {:.1}% faster than the baseline of {}; consider updating it",
+                    name, cycles, -change_percent, baseline
+                );
+            }
+            Comparison::Slower {
+                baseline,
+                change_percent,
+            } => {
+                failures.push(format!(
+                    "{}: {} cycles, {:.1}% over the baseline of {}",
+                    name, cycles, change_percent, baseline
+                ));
+            }
+            Comparison::Within { .. } => {}
+        }
+    }
+
+    if baseline::update_requested() {
+        baseline.cycles = measured.into_iter().collect();
+        baseline.save(&path).unwrap();
+        eprintln!("recorded the measured cycle counts in {}", path.display());
+        return;
+    }
+
+    assert!(
+        failures.is_empty(),
+        "the cycle counts do not match the baseline (tolerance {}%):\n{}\nrun \
+         `sxg baseline update`, or this test with {}=1, if this is intentional",
+        baseline.tolerance_percent,
+        failures.join("\n"),
+        UPDATE_ENV
+    );
+}
@@ -0,0 +1,47 @@
+//! Checks that the cycle model can be fitted to inputs that differ only in size.
+
+use sxg_script::{
+    baseline::{self, BUNDLED_INPUTS},
+    estimate::{CycleModel, InputFeatures, Sample},
+};
+
+fn sample(hashed_bytes: u64, committed_bytes: u64) -> Sample {
+    let features = InputFeatures {
+        hashed_bytes,
+        ecdsa_verifications: 1,
+        committed_bytes,
+    };
+    Sample {
+        features,
+        cycles: 9 * hashed_bytes + 1_500_000 + 40 * committed_bytes,
+    }
+}
+
+#[test]
+fn fit_recovers_costs() {
+    // Payloads under one record all have one record, so a per-record model could not be fitted
+    // to these.
+    let samples = [sample(900, 320), sample(4_000, 384), sample(12_000, 352)];
+    let model = CycleModel::fit(&samples).unwrap();
+    assert!((model.per_hashed_byte - 9.0).abs() < 1e-6);
+    assert!((model.per_ecdsa_verification - 1_500_000.0).abs() < 1e-3);
+    assert!((model.per_committed_byte - 40.0).abs() < 1e-6);
+    assert_eq!(model.samples, 3);
+
+    for sample in &samples {
+        assert_eq!(model.estimate(&sample.features), sample.cycles);
+    }
+}
+
+#[test]
+fn fit_needs_distinct_sizes() {
+    assert!(CycleModel::fit(&[sample(900, 320), sample(900, 320), sample(900, 320)]).is_err());
+}
+
+#[test]
+fn features_count_hashed_bytes() {
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+    let features = InputFeatures::of(&input);
+    assert!(features.hashed_bytes > (input.payload.len() + input.final_payload.len()) as u64);
+    assert_eq!(features.ecdsa_verifications, 1);
+}
@@ -0,0 +1,85 @@
+//! Mock-proves the bundled inputs and checks the mock fixtures the Foundry tests read are what the
+//! current program commits. Run with `SXG_UPDATE_FIXTURES=1` to regenerate them.
+
+use std::path::{Path, PathBuf};
+
+use alloy_sol_types::SolType;
+use lib::PublicValuesStruct;
+use sxg_script::{
+    baseline::{self, BUNDLED_INPUTS},
+    prover::{ProofKind, SxgProver},
+};
+
+const UPDATE_ENV: &str = "SXG_UPDATE_FIXTURES";
+
+fn fixture_path(kind: ProofKind) -> PathBuf {
+    let name = match kind {
+        ProofKind::Groth16 => "mock-groth16-fixture.json",
+        ProofKind::Plonk => "mock-plonk-fixture.json",
+        _ => unreachable!("only groth16 and plonk proofs have fixtures"),
+    };
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../contracts/src/fixtures")
+        .join(name)
+}
+
+#[test]
+fn mock_fixture_commits_input_public_values() {
+    let prover = SxgProver::mock();
+    for name in BUNDLED_INPUTS {
+        let input = baseline::load_bundled(name).unwrap();
+        let fixture = prover
+            .prove(&input, ProofKind::Groth16)
+            .unwrap()
+            .fixture()
+            .unwrap();
+
+        let expected = PublicValuesStruct::abi_encode(&input.public_values(true));
+        assert_eq!(
+            fixture.public_values,
+            format!("0x{}", hex::encode(expected))
+        );
+        assert_eq!(fixture.result, 1);
+        assert_eq!(fixture.vkey, prover.vkey());
+        // A mock proof carries no proof data, at most the zeroed verifier selector.
+        assert!(
+            fixture
+                .proof
+                .trim_start_matches("0x")
+                .bytes()
+                .all(|b| b == b'0'),
+            "{}",
+            fixture.proof
+        );
+    }
+}
+
+#[test]
+fn foundry_mock_fixtures_are_current() {
+    let prover = SxgProver::mock();
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+    let update = std::env::var(UPDATE_ENV).is_ok_and(|value| value == "1");
+
+    let mut stale = Vec::new();
+    for kind in [ProofKind::Groth16, ProofKind::Plonk] {
+        let fixture = prover.prove(&input, kind).unwrap().fixture().unwrap();
+        let path = fixture_path(kind);
+        if update {
+            sxg_script::write_json(&path, &fixture).unwrap();
+            continue;
+        }
+        let current = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+        if current != Some(serde_json::to_value(&fixture).unwrap()) {
+            stale.push(path.display().to_string());
+        }
+    }
+
+    assert!(
+        stale.is_empty(),
+        "mock fixtures are missing or out of date: {}\nrun this test with {}=1 to regenerate them",
+        stale.join(", "),
+        UPDATE_ENV
+    );
+}
@@ -0,0 +1,78 @@
+//! Proves a bundled input with the mock prover and checks its bundle round-trips through
+//! [`SxgProver::verify`].
+
+use sxg_script::{
+    baseline::{self, BUNDLED_INPUTS},
+    bundle::ProofBundle,
+    prover::{Progress, ProofKind, SxgProver},
+};
+
+#[test]
+fn mock_proof_bundle_verifies() {
+    let prover = SxgProver::mock();
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+
+    let execution = prover.execute(&input).unwrap();
+    assert_eq!(execution.public_values.result, 1);
+
+    let proof = prover.prove(&input, ProofKind::Groth16).unwrap();
+    assert!(!proof.cached);
+    assert_eq!(proof.public_values.url, execution.public_values.url);
+
+    let dir = std::env::temp_dir().join(format!("sxg-prover-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("proof.sxgproof");
+    proof.bundle.write(&path).unwrap();
+    let bundle = ProofBundle::read(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let verification = prover.verify(&bundle).unwrap();
+    assert_eq!(verification.proof_type, ProofKind::Groth16);
+    assert_eq!(verification.vkey, prover.vkey());
+    assert_eq!(verification.public_values.result, 1);
+
+    let mut tampered = bundle.clone();
+    tampered.public_values = format!("{}00", tampered.public_values);
+    assert!(prover.verify(&tampered).is_err());
+
+    let mut foreign = bundle;
+    foreign.vkey = format!("0x{}", "00".repeat(32));
+    assert!(prover.verify(&foreign).is_err());
+}
+
+#[test]
+fn mock_proof_reports_progress() {
+    let prover = SxgProver::mock();
+    let input = baseline::load_bundled(BUNDLED_INPUTS[0]).unwrap();
+
+    let mut stages = Vec::new();
+    let proof = prover
+        .prove_with_progress(&input, ProofKind::Groth16, |stage| {
+            stages.push(stage);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(proof.public_values.result, 1);
+
+    assert_eq!(stages.len(), 4);
+    assert_eq!(stages[0], Progress::Executing);
+    assert!(matches!(
+        stages[1],
+        Progress::Proving { kind: ProofKind::Groth16, cycles, .. } if cycles > 0
+    ));
+    assert_eq!(
+        stages[2..],
+        [
+            Progress::Wrapping {
+                kind: ProofKind::Groth16
+            },
+            Progress::Verifying
+        ]
+    );
+
+    let stop = prover.prove_with_progress(&input, ProofKind::Groth16, |stage| match stage {
+        Progress::Proving { .. } => Err("stopped".into()),
+        _ => Ok(()),
+    });
+    assert_eq!(stop.err().unwrap().to_string(), "stopped");
+}